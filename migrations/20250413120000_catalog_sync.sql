@@ -0,0 +1,18 @@
+-- Freshness bookkeeping for incremental catalog syncs
+
+CREATE TABLE catalog_sync_state (
+    provider TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    last_incremental_sync TIMESTAMPTZ,
+    last_full_sync TIMESTAMPTZ,
+    PRIMARY KEY (provider, entity)
+);
+
+CREATE TABLE catalog_fingerprints (
+    provider TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    fingerprint BIGINT NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, entity, id)
+);
@@ -0,0 +1,17 @@
+-- Checkpoints for resumable catalog scrapes
+
+CREATE TABLE scrape_runs (
+    id BIGSERIAL PRIMARY KEY,
+    provider TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    full_sync BOOLEAN NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running',
+    last_page INTEGER NOT NULL DEFAULT 0,
+    failed_pages INTEGER[] NOT NULL DEFAULT '{}',
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX scrape_runs_running ON scrape_runs (provider, entity) WHERE status = 'running';
@@ -0,0 +1,17 @@
+-- Canonical identities shared by the same work across providers
+
+CREATE TABLE canonical_ids (
+    id BIGSERIAL PRIMARY KEY,
+    entity TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE provider_ids (
+    provider TEXT NOT NULL,
+    entity TEXT NOT NULL,
+    provider_id INTEGER NOT NULL,
+    canonical_id BIGINT NOT NULL REFERENCES canonical_ids (id) ON DELETE CASCADE,
+    PRIMARY KEY (provider, entity, provider_id)
+);
+
+CREATE INDEX provider_ids_canonical ON provider_ids (canonical_id);
@@ -0,0 +1,69 @@
+-- Scraped catalog records, the source Meilisearch indexes are built from
+
+CREATE TABLE catalog_media (
+    provider TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    mal_id INTEGER,
+    url TEXT NOT NULL,
+    image_url TEXT,
+    media_type TEXT,
+    status TEXT,
+    episodes INTEGER,
+    chapters INTEGER,
+    volumes INTEGER,
+    score REAL,
+    rank INTEGER,
+    popularity INTEGER,
+    favorites INTEGER,
+    synopsis TEXT,
+    season TEXT,
+    year INTEGER,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, kind, id)
+);
+
+CREATE TABLE catalog_media_titles (
+    provider TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    media_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    title_type TEXT NOT NULL,
+    title TEXT NOT NULL,
+    PRIMARY KEY (provider, kind, media_id, position),
+    FOREIGN KEY (provider, kind, media_id) REFERENCES catalog_media (provider, kind, id) ON DELETE CASCADE
+);
+
+-- Genres, tags and creators
+CREATE TABLE catalog_media_terms (
+    provider TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    media_id INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (provider, kind, media_id, category, position),
+    FOREIGN KEY (provider, kind, media_id) REFERENCES catalog_media (provider, kind, id) ON DELETE CASCADE
+);
+
+CREATE TABLE catalog_characters (
+    provider TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    image_url TEXT,
+    name TEXT NOT NULL,
+    name_native TEXT,
+    favorites INTEGER,
+    about TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, id)
+);
+
+CREATE TABLE catalog_character_nicknames (
+    provider TEXT NOT NULL,
+    character_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    nickname TEXT NOT NULL,
+    PRIMARY KEY (provider, character_id, position),
+    FOREIGN KEY (provider, character_id) REFERENCES catalog_characters (provider, id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- Age rating, used as a search facet
+
+ALTER TABLE catalog_media ADD COLUMN rating TEXT;
@@ -0,0 +1,16 @@
+-- Postgres full-text search over the catalog, used when Meilisearch is unavailable
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE catalog_media ADD COLUMN synopsis_search TSVECTOR
+    GENERATED ALWAYS AS (to_tsvector('english', COALESCE(synopsis, ''))) STORED;
+
+CREATE INDEX catalog_media_synopsis_search ON catalog_media USING GIN (synopsis_search);
+CREATE INDEX catalog_media_titles_trgm ON catalog_media_titles USING GIN (title gin_trgm_ops);
+CREATE INDEX catalog_media_synonyms_trgm ON catalog_media_terms USING GIN (name gin_trgm_ops)
+    WHERE category = 'synonym';
+CREATE INDEX catalog_characters_name_trgm ON catalog_characters USING GIN (name gin_trgm_ops);
+CREATE INDEX catalog_characters_name_native_trgm ON catalog_characters
+    USING GIN (name_native gin_trgm_ops);
+CREATE INDEX catalog_character_nicknames_trgm ON catalog_character_nicknames
+    USING GIN (nickname gin_trgm_ops);
@@ -0,0 +1,6 @@
+-- Titles in their search form (width, kana and romanization folded), written by the application
+
+ALTER TABLE catalog_media_titles ADD COLUMN normalized TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX catalog_media_titles_normalized_trgm ON catalog_media_titles
+    USING GIN (normalized gin_trgm_ops);
@@ -0,0 +1,15 @@
+-- Anonymized search analytics: events carry no user, session or address, and are purged
+-- after the configured retention period
+
+CREATE TABLE search_events (
+    id BIGSERIAL PRIMARY KEY,
+    query TEXT NOT NULL,
+    category TEXT NOT NULL,
+    filters TEXT NOT NULL DEFAULT '',
+    result_count INTEGER NOT NULL,
+    clicked_result TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX search_events_created_at ON search_events (created_at);
+CREATE INDEX search_events_query ON search_events (query, category);
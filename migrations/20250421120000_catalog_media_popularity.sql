@@ -0,0 +1,5 @@
+-- Jikan records stored MAL's popularity rank, where AniList stores a member count. Both now
+-- store the member count, so drop the stale ranks until the next sync refetches them.
+
+UPDATE catalog_media SET popularity = NULL WHERE provider = 'jikan';
+DELETE FROM catalog_fingerprints WHERE provider = 'jikan' AND entity IN ('anime', 'manga');
@@ -0,0 +1,7 @@
+-- Character names in their search form (width, kana and romanization folded), written by the
+-- application
+
+ALTER TABLE catalog_characters ADD COLUMN normalized TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX catalog_characters_normalized_trgm ON catalog_characters
+    USING GIN (normalized gin_trgm_ops);
@@ -1,111 +1,92 @@
-use crate::apis::anilist::anime::model::Anime;
-use leptos::prelude::*;
+use crate::{
+    apis::{
+        anilist::{
+            anime::model::Anime,
+            shared::{ANILIST_PER_PAGE, all_pages, fetch_page, pages_until, search_page},
+        },
+        provider::{CatalogPage, SearchHits},
+    },
+    errors::error_template::AppError,
+};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/anime/query.graphql"
+)]
+pub struct AnimeQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/anime/query.graphql"
+)]
+pub struct AnimeSearchQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/anime/query.graphql"
+)]
+pub struct AnimeUpdatedQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/anime/query.graphql"
+)]
+pub struct AnimeOngoingQuery;
 
-#[server]
-pub async fn search_anime(query: String) -> Result<Vec<Anime>, ServerFnError> {
-    use crate::config::types::MEILISEARCH_CLIENT;
-    if let Some(meilisearch_client) = MEILISEARCH_CLIENT.get()
-        && let Ok(index) = meilisearch_client.get_index("jikan_anime").await
-        && let Ok(results) = index
-            .search()
-            .with_query(query.as_str())
-            .execute::<Anime>()
-            .await
-    {
-        Ok(results.hits.into_iter().map(|s| s.result).collect())
-    } else {
-        search_anime_api(query).await
-    }
+pub async fn search_anime_api(query: &str, page: i32) -> Result<SearchHits<Anime>, AppError> {
+    let variables = anime_search_query::Variables {
+        id: None,
+        search: Some(query.to_owned()),
+        page: Some(page.into()),
+    };
+    search_page::<AnimeSearchQuery, Anime>(variables).await
 }
 
-#[server]
-pub async fn search_anime_api(query: String) -> Result<Vec<Anime>, ServerFnError> {
-    use crate::config::types::HTTP_CLIENT;
-    use graphql_client::{GraphQLQuery, Response};
-    use leptos::logging::log;
-    #[derive(GraphQLQuery)]
-    #[graphql(
-        schema_path = "src/apis/anilist/schema.json",
-        query_path = "src/apis/anilist/anime/query.graphql",
-        response_derives = "Debug"
-    )]
-    struct MediaQuery;
-    let variables = media_query::Variables { search: query };
-    match HTTP_CLIENT
-        .post("https://graphql.anilist.co/")
-        .json(&MediaQuery::build_query(variables))
-        .send()
-        .await
-    {
-        Ok(resp) => match resp.json::<Response<media_query::ResponseData>>().await {
-            Ok(parsed) => {
-                if let Some(data) = parsed.data
-                    && let Some(page) = data.page
-                    && let Some(media) = page.media
-                {
-                    for anime in media.iter().flatten() {
-                        log!("---");
-                        log!("ID: {}", anime.id);
+pub async fn anime_by_id(id: i32) -> Result<Option<Anime>, AppError> {
+    let variables = anime_search_query::Variables {
+        id: Some(id.into()),
+        search: None,
+        page: None,
+    };
+    Ok(fetch_page::<AnimeSearchQuery, Anime>(variables)
+        .await?
+        .items
+        .into_iter()
+        .next())
+}
 
-                        if let Some(title) = &anime.title {
-                            log!("Romaji: {}", title.romaji.as_deref().unwrap_or("N/A"));
-                            log!("English: {}", title.english.as_deref().unwrap_or("N/A"));
-                            log!("Native: {}", title.native.as_deref().unwrap_or("N/A"));
-                        }
-                    }
-                } else {
-                    log!("No media found");
-                }
-                Ok(Vec::default())
-            }
-            Err(_) => {
-                log!("No data received");
-                Ok(Vec::default())
-            }
-        },
-        Err(_) => Ok(Vec::default()),
-    }
+pub async fn anime_page(page: i32) -> Result<CatalogPage<Anime>, AppError> {
+    let variables = anime_query::Variables {
+        page: page.into(),
+        per_page: ANILIST_PER_PAGE,
+    };
+    let page = fetch_page::<AnimeQuery, Anime>(variables).await?;
+    Ok(CatalogPage {
+        items: page.items,
+        has_next_page: page.page_info.has_next_page.unwrap_or(false),
+    })
+}
+
+pub async fn ongoing_anime() -> Result<Vec<Anime>, AppError> {
+    all_pages::<AnimeOngoingQuery, Anime>(|page| anime_ongoing_query::Variables {
+        page,
+        per_page: ANILIST_PER_PAGE,
+    })
+    .await
 }
 
-#[server]
-pub async fn all_anime() -> Result<Vec<Anime>, ServerFnError> {
-    use crate::{apis::anilist::anime::model::AnimeResponse, config::types::HTTP_CLIENT};
-    use leptos::logging::log;
-    match HTTP_CLIENT
-        .get("https://api.jikan.moe/v4/anime")
-        .send()
-        .await
-    {
-        Ok(resp) => match resp.json::<AnimeResponse>().await {
-            Ok(data) if !data.data.is_empty() => {
-                let mut all_anime: Vec<Anime> = Vec::with_capacity(data.pagination.items.total);
-                let mut current_page = 1;
-                loop {
-                    let request_url = format!("https://api.jikan.moe/v4/anime?page={current_page}");
-                    match HTTP_CLIENT.get(&request_url).send().await {
-                        Ok(resp) => match resp.json::<AnimeResponse>().await {
-                            Ok(data) => {
-                                all_anime.extend(data.data);
-                                if !data.pagination.has_next_page {
-                                    break;
-                                }
-                                current_page += 1;
-                            }
-                            Err(e) => {
-                                log!("Parsing error: {e}");
-                                break;
-                            }
-                        },
-                        Err(e) => {
-                            log!("Request error: {e}");
-                            break;
-                        }
-                    }
-                }
-                Ok(all_anime)
-            }
-            Ok(_) | Err(_) => Ok(Vec::default()),
+pub async fn updated_anime(since: Option<i64>) -> Result<Vec<Anime>, AppError> {
+    pages_until::<AnimeUpdatedQuery, Anime>(
+        |page| anime_updated_query::Variables {
+            page,
+            per_page: ANILIST_PER_PAGE,
         },
-        Err(_) => Ok(Vec::default()),
-    }
+        |anime| anime.updated_at <= since,
+    )
+    .await
 }
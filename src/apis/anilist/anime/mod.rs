@@ -1,2 +1,9 @@
-pub mod fetch;
+use cfg_if::cfg_if;
+
 pub mod model;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod fetch;
+    }
+}
@@ -1,76 +1,57 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeResponse {
-    pub pagination: AnimePagination,
-    pub data: Vec<Anime>,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimePagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: AnimeItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeItems {
-    pub count: i32,
-    pub total: usize,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Anime {
-    pub mal_id: i32,
-    pub url: String,
-    pub images: AnimeImageTypes,
-    pub trailer: AnimeTrailerInfo,
-    pub titles: Vec<AnimeTitleTypes>,
-    #[serde(rename = "type")]
-    pub anime_type: String,
-    pub source: Option<String>,
+    pub id: i32,
+    pub id_mal: Option<i32>,
+    pub site_url: Option<String>,
+    pub updated_at: Option<i64>,
+    pub title: Option<AnimeTitle>,
+    pub synonyms: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub status: Option<String>,
+    pub is_adult: Option<bool>,
     pub episodes: Option<i32>,
-    pub status: String,
-    pub aired: AnimeAired,
-    pub duration: Option<String>,
-    pub score: Option<f32>,
-    pub rank: Option<i32>,
+    pub season: Option<String>,
+    pub season_year: Option<i32>,
+    pub average_score: Option<i32>,
     pub popularity: Option<i32>,
-    pub favorites: Option<i32>,
-    pub synopsis: Option<String>,
-    pub background: Option<String>,
-    pub genres: Vec<AnimeGenres>,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeImageTypes {
-    pub webp: AnimeImageWebp,
+    pub favourites: Option<i32>,
+    pub description: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub tags: Option<Vec<AnimeTag>>,
+    pub cover_image: Option<AnimeCoverImage>,
+    pub studios: Option<AnimeStudios>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeImageWebp {
-    pub image_url: String,
+pub struct AnimeTitle {
+    pub romaji: Option<String>,
+    pub english: Option<String>,
+    pub native: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeTrailerInfo {
-    pub url: Option<String>,
+#[serde(rename_all = "camelCase")]
+pub struct AnimeCoverImage {
+    pub extra_large: Option<String>,
+    pub large: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeTitleTypes {
-    #[serde(rename = "type")]
-    pub title_type: String,
-    pub title: String,
+pub struct AnimeStudios {
+    pub nodes: Option<Vec<AnimeStudio>>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeAired {
-    #[serde(rename = "string")]
-    pub aired_string: Option<String>,
+pub struct AnimeStudio {
+    pub name: String,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeGenres {
+#[serde(rename_all = "camelCase")]
+pub struct AnimeTag {
     pub name: String,
+    pub is_media_spoiler: Option<bool>,
 }
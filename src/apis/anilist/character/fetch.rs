@@ -1,77 +1,80 @@
-use crate::apis::anilist::character::model::Character;
-use leptos::prelude::*;
+use crate::{
+    apis::{
+        anilist::{
+            character::model::Character,
+            shared::{ANILIST_PER_PAGE, fetch_page, pages_until, search_page},
+        },
+        provider::{CatalogPage, SearchHits},
+    },
+    errors::error_template::AppError,
+};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/character/query.graphql"
+)]
+pub struct CharacterQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/character/query.graphql"
+)]
+pub struct CharacterSearchQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/character/query.graphql"
+)]
+pub struct CharacterNewestQuery;
 
-#[server]
-pub async fn search_character(query: String) -> Result<Vec<Character>, ServerFnError> {
-    use crate::config::types::MEILISEARCH_CLIENT;
-    if let Some(meilisearch_client) = MEILISEARCH_CLIENT.get()
-        && let Ok(index) = meilisearch_client.get_index("jikan_character").await
-        && let Ok(results) = index
-            .search()
-            .with_query(query.as_str())
-            .execute::<Character>()
-            .await
-    {
-        Ok(results.hits.into_iter().map(|s| s.result).collect())
-    } else {
-        search_character_api(query).await
-    }
+pub async fn search_character_api(
+    query: &str,
+    page: i32,
+) -> Result<SearchHits<Character>, AppError> {
+    let variables = character_search_query::Variables {
+        id: None,
+        search: Some(query.to_owned()),
+        page: Some(page.into()),
+    };
+    search_page::<CharacterSearchQuery, Character>(variables).await
 }
 
-#[server]
-pub async fn search_character_api(query: String) -> Result<Vec<Character>, ServerFnError> {
-    use crate::{apis::anilist::character::model::CharacterResponse, config::types::HTTP_CLIENT};
-    let request_url = format!("https://api.jikan.moe/v4/character?q={query}");
-    match HTTP_CLIENT.get(request_url).send().await {
-        Ok(resp) => match resp.json::<CharacterResponse>().await {
-            Ok(data) if !data.data.is_empty() => Ok(data.data),
-            Ok(_) | Err(_) => Ok(Vec::default()),
-        },
-        Err(_) => Ok(Vec::default()),
-    }
+pub async fn character_by_id(id: i32) -> Result<Option<Character>, AppError> {
+    let variables = character_search_query::Variables {
+        id: Some(id.into()),
+        search: None,
+        page: None,
+    };
+    Ok(fetch_page::<CharacterSearchQuery, Character>(variables)
+        .await?
+        .items
+        .into_iter()
+        .next())
+}
+
+pub async fn character_page(page: i32) -> Result<CatalogPage<Character>, AppError> {
+    let variables = character_query::Variables {
+        page: page.into(),
+        per_page: ANILIST_PER_PAGE,
+    };
+    let page = fetch_page::<CharacterQuery, Character>(variables).await?;
+    Ok(CatalogPage {
+        items: page.items,
+        has_next_page: page.page_info.has_next_page.unwrap_or(false),
+    })
 }
 
-#[server]
-pub async fn all_character() -> Result<Vec<Character>, ServerFnError> {
-    use crate::{apis::anilist::character::model::CharacterResponse, config::types::HTTP_CLIENT};
-    use leptos::logging::log;
-    match HTTP_CLIENT
-        .get("https://api.jikan.moe/v4/character")
-        .send()
-        .await
-    {
-        Ok(resp) => match resp.json::<CharacterResponse>().await {
-            Ok(data) if !data.data.is_empty() => {
-                let mut all_character: Vec<Character> =
-                    Vec::with_capacity(data.pagination.items.total);
-                let mut current_page = 1;
-                loop {
-                    let request_url =
-                        format!("https://api.jikan.moe/v4/character?page={current_page}");
-                    match HTTP_CLIENT.get(&request_url).send().await {
-                        Ok(resp) => match resp.json::<CharacterResponse>().await {
-                            Ok(data) => {
-                                all_character.extend(data.data);
-                                if !data.pagination.has_next_page {
-                                    break;
-                                }
-                                current_page += 1;
-                            }
-                            Err(e) => {
-                                log!("Parsing error: {e}");
-                                break;
-                            }
-                        },
-                        Err(e) => {
-                            log!("Request error: {e}");
-                            break;
-                        }
-                    }
-                }
-                Ok(all_character)
-            }
-            Ok(_) | Err(_) => Ok(Vec::default()),
+pub async fn newest_character(known_id: Option<i32>) -> Result<Vec<Character>, AppError> {
+    pages_until::<CharacterNewestQuery, Character>(
+        |page| character_newest_query::Variables {
+            page,
+            per_page: ANILIST_PER_PAGE,
         },
-        Err(_) => Ok(Vec::default()),
-    }
+        |character| Some(character.id) <= known_id,
+    )
+    .await
 }
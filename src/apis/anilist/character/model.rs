@@ -1,41 +1,24 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterResponse {
-    pub pagination: CharacterPagination,
-    pub data: Vec<Character>,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterPagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: CharacterItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterItems {
-    pub count: i32,
-    pub total: usize,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Character {
-    pub mal_id: i32,
-    pub url: String,
-    pub images: CharacterImageTypes,
-    pub name: String,
-    pub name_kanji: Option<String>,
-    pub nicknames: Vec<String>,
-    pub about: Option<String>,
+    pub id: i32,
+    pub site_url: Option<String>,
+    pub name: Option<CharacterName>,
+    pub image: Option<CharacterImage>,
+    pub description: Option<String>,
+    pub favourites: Option<i32>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterImageTypes {
-    pub webp: CharacterImageWebp,
+pub struct CharacterName {
+    pub full: Option<String>,
+    pub native: Option<String>,
+    pub alternative: Option<Vec<String>>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterImageWebp {
-    pub image_url: String,
+pub struct CharacterImage {
+    pub large: Option<String>,
 }
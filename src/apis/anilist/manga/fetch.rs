@@ -1,75 +1,92 @@
-use crate::apis::anilist::manga::model::Manga;
-use leptos::prelude::*;
+use crate::{
+    apis::{
+        anilist::{
+            manga::model::Manga,
+            shared::{ANILIST_PER_PAGE, all_pages, fetch_page, pages_until, search_page},
+        },
+        provider::{CatalogPage, SearchHits},
+    },
+    errors::error_template::AppError,
+};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/manga/query.graphql"
+)]
+pub struct MangaQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/manga/query.graphql"
+)]
+pub struct MangaSearchQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/manga/query.graphql"
+)]
+pub struct MangaUpdatedQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/manga/query.graphql"
+)]
+pub struct MangaOngoingQuery;
 
-#[server]
-pub async fn search_manga(query: String) -> Result<Vec<Manga>, ServerFnError> {
-    use crate::config::types::MEILISEARCH_CLIENT;
-    if let Some(meilisearch_client) = MEILISEARCH_CLIENT.get()
-        && let Ok(index) = meilisearch_client.get_index("jikan_manga").await
-        && let Ok(results) = index
-            .search()
-            .with_query(query.as_str())
-            .execute::<Manga>()
-            .await
-    {
-        Ok(results.hits.into_iter().map(|s| s.result).collect())
-    } else {
-        search_manga_api(query).await
-    }
+pub async fn search_manga_api(query: &str, page: i32) -> Result<SearchHits<Manga>, AppError> {
+    let variables = manga_search_query::Variables {
+        id: None,
+        search: Some(query.to_owned()),
+        page: Some(page.into()),
+    };
+    search_page::<MangaSearchQuery, Manga>(variables).await
 }
 
-#[server]
-pub async fn search_manga_api(query: String) -> Result<Vec<Manga>, ServerFnError> {
-    use crate::{apis::anilist::manga::model::MangaResponse, config::types::HTTP_CLIENT};
-    let request_url = format!("https://api.jikan.moe/v4/manga?q={query}");
-    match HTTP_CLIENT.get(request_url).send().await {
-        Ok(resp) => match resp.json::<MangaResponse>().await {
-            Ok(data) if !data.data.is_empty() => Ok(data.data),
-            Ok(_) | Err(_) => Ok(Vec::default()),
-        },
-        Err(_) => Ok(Vec::default()),
-    }
+pub async fn manga_by_id(id: i32) -> Result<Option<Manga>, AppError> {
+    let variables = manga_search_query::Variables {
+        id: Some(id.into()),
+        search: None,
+        page: None,
+    };
+    Ok(fetch_page::<MangaSearchQuery, Manga>(variables)
+        .await?
+        .items
+        .into_iter()
+        .next())
+}
+
+pub async fn manga_page(page: i32) -> Result<CatalogPage<Manga>, AppError> {
+    let variables = manga_query::Variables {
+        page: page.into(),
+        per_page: ANILIST_PER_PAGE,
+    };
+    let page = fetch_page::<MangaQuery, Manga>(variables).await?;
+    Ok(CatalogPage {
+        items: page.items,
+        has_next_page: page.page_info.has_next_page.unwrap_or(false),
+    })
+}
+
+pub async fn ongoing_manga() -> Result<Vec<Manga>, AppError> {
+    all_pages::<MangaOngoingQuery, Manga>(|page| manga_ongoing_query::Variables {
+        page,
+        per_page: ANILIST_PER_PAGE,
+    })
+    .await
 }
 
-#[server]
-pub async fn all_manga() -> Result<Vec<Manga>, ServerFnError> {
-    use crate::{apis::anilist::manga::model::MangaResponse, config::types::HTTP_CLIENT};
-    use leptos::logging::log;
-    match HTTP_CLIENT
-        .get("https://api.jikan.moe/v4/manga")
-        .send()
-        .await
-    {
-        Ok(resp) => match resp.json::<MangaResponse>().await {
-            Ok(data) if !data.data.is_empty() => {
-                let mut all_manga: Vec<Manga> = Vec::with_capacity(data.pagination.items.total);
-                let mut current_page = 1;
-                loop {
-                    let request_url = format!("https://api.jikan.moe/v4/manga?page={current_page}");
-                    match HTTP_CLIENT.get(&request_url).send().await {
-                        Ok(resp) => match resp.json::<MangaResponse>().await {
-                            Ok(data) => {
-                                all_manga.extend(data.data);
-                                if !data.pagination.has_next_page {
-                                    break;
-                                }
-                                current_page += 1;
-                            }
-                            Err(e) => {
-                                log!("Parsing error: {e}");
-                                break;
-                            }
-                        },
-                        Err(e) => {
-                            log!("Request error: {e}");
-                            break;
-                        }
-                    }
-                }
-                Ok(all_manga)
-            }
-            Ok(_) | Err(_) => Ok(Vec::default()),
+pub async fn updated_manga(since: Option<i64>) -> Result<Vec<Manga>, AppError> {
+    pages_until::<MangaUpdatedQuery, Manga>(
+        |page| manga_updated_query::Variables {
+            page,
+            per_page: ANILIST_PER_PAGE,
         },
-        Err(_) => Ok(Vec::default()),
-    }
+        |manga| manga.updated_at <= since,
+    )
+    .await
 }
@@ -1,75 +1,67 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaResponse {
-    pub data: Vec<Manga>,
-    pub pagination: MangaPagination,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct MangaPagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: MangaItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct MangaItems {
-    pub count: i32,
-    pub total: usize,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct Manga {
-    pub url: String,
-    pub images: MangaImageTypes,
-    pub trailer: MangaTrailerInfo,
-    pub titles: Vec<MangaTitleTypes>,
-    #[serde(rename = "type")]
-    pub manga_type: String,
+    pub id: i32,
+    pub id_mal: Option<i32>,
+    pub site_url: Option<String>,
+    pub updated_at: Option<i64>,
+    pub title: Option<MangaTitle>,
+    pub synonyms: Option<Vec<String>>,
+    pub format: Option<String>,
+    pub status: Option<String>,
+    pub is_adult: Option<bool>,
     pub chapters: Option<i32>,
     pub volumes: Option<i32>,
-    pub status: String,
-    pub published: MangaAired,
-    pub duration: Option<String>,
-    pub score: Option<f32>,
-    pub rank: Option<i32>,
+    pub start_date: Option<MangaStartDate>,
+    pub average_score: Option<i32>,
     pub popularity: Option<i32>,
-    pub favorites: Option<i32>,
-    pub synopsis: Option<String>,
-    pub background: Option<String>,
-    pub genres: Vec<MangaGenres>,
+    pub favourites: Option<i32>,
+    pub description: Option<String>,
+    pub genres: Option<Vec<String>>,
+    pub tags: Option<Vec<MangaTag>>,
+    pub cover_image: Option<MangaCoverImage>,
+    pub staff: Option<MangaStaff>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct MangaTitle {
+    pub romaji: Option<String>,
+    pub english: Option<String>,
+    pub native: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaImageTypes {
-    pub webp: MangaImageWebp,
+pub struct MangaStartDate {
+    pub year: Option<i32>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaImageWebp {
-    pub image_url: String,
+#[serde(rename_all = "camelCase")]
+pub struct MangaCoverImage {
+    pub extra_large: Option<String>,
+    pub large: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaTrailerInfo {
-    pub url: Option<String>,
+pub struct MangaStaff {
+    pub nodes: Option<Vec<MangaStaffMember>>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaTitleTypes {
-    #[serde(rename = "type")]
-    pub title_type: String,
-    pub title: String,
+pub struct MangaStaffMember {
+    pub name: Option<MangaStaffName>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaAired {
-    #[serde(rename = "string")]
-    pub aired_string: Option<String>,
+pub struct MangaStaffName {
+    pub full: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
-pub struct MangaGenres {
+#[serde(rename_all = "camelCase")]
+pub struct MangaTag {
     pub name: String,
+    pub is_media_spoiler: Option<bool>,
 }
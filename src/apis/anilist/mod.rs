@@ -1,4 +1,13 @@
+use cfg_if::cfg_if;
+
 pub mod anime;
 pub mod character;
 pub mod manga;
-pub mod tracker;
+pub mod shared;
+pub mod staff;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod provider;
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    apis::{
+        anilist::{
+            anime::{
+                fetch::*,
+                model::{Anime, AnimeTitle},
+            },
+            character::{fetch::*, model::Character as AnilistCharacter},
+            manga::{
+                fetch::*,
+                model::{Manga, MangaTitle},
+            },
+            staff::{fetch::*, model::Staff},
+        },
+        model::{Character, Media, MediaKind, MediaTitle, Person, Provider},
+        provider::{CatalogPage, MetadataProvider, SearchHits, SyncCursor},
+    },
+    errors::error_template::AppError,
+};
+use async_trait::async_trait;
+
+const ADULT_RATING: &str = "Rx - Hentai";
+
+pub struct AnilistProvider;
+
+#[async_trait]
+impl MetadataProvider for AnilistProvider {
+    fn provider(&self) -> Provider {
+        Provider::Anilist
+    }
+
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => search_anime_api(query, page).await?.map(Media::from),
+            MediaKind::Manga => search_manga_api(query, page).await?.map(Media::from),
+        })
+    }
+
+    async fn media_by_id(&self, kind: MediaKind, id: i32) -> Result<Option<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => anime_by_id(id).await?.map(Media::from),
+            MediaKind::Manga => manga_by_id(id).await?.map(Media::from),
+        })
+    }
+
+    async fn media_page(&self, kind: MediaKind, page: i32) -> Result<CatalogPage<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => {
+                let page = anime_page(page).await?;
+                CatalogPage {
+                    items: page.items.into_iter().map(Media::from).collect(),
+                    has_next_page: page.has_next_page,
+                }
+            }
+            MediaKind::Manga => {
+                let page = manga_page(page).await?;
+                CatalogPage {
+                    items: page.items.into_iter().map(Media::from).collect(),
+                    has_next_page: page.has_next_page,
+                }
+            }
+        })
+    }
+
+    async fn updated_media(
+        &self,
+        kind: MediaKind,
+        cursor: SyncCursor,
+    ) -> Result<Vec<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => ongoing_anime()
+                .await?
+                .into_iter()
+                .chain(updated_anime(cursor.last_synced_at).await?)
+                .map(Media::from)
+                .collect(),
+            MediaKind::Manga => ongoing_manga()
+                .await?
+                .into_iter()
+                .chain(updated_manga(cursor.last_synced_at).await?)
+                .map(Media::from)
+                .collect(),
+        })
+    }
+
+    async fn search_characters(
+        &self,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError> {
+        Ok(search_character_api(query, page)
+            .await?
+            .map(Character::from))
+    }
+
+    async fn character_by_id(&self, id: i32) -> Result<Option<Character>, AppError> {
+        Ok(character_by_id(id).await?.map(Character::from))
+    }
+
+    async fn character_page(&self, page: i32) -> Result<CatalogPage<Character>, AppError> {
+        let page = character_page(page).await?;
+        Ok(CatalogPage {
+            items: page.items.into_iter().map(Character::from).collect(),
+            has_next_page: page.has_next_page,
+        })
+    }
+
+    async fn updated_characters(&self, cursor: SyncCursor) -> Result<Vec<Character>, AppError> {
+        Ok(newest_character(cursor.known_id)
+            .await?
+            .into_iter()
+            .map(Character::from)
+            .collect())
+    }
+
+    async fn search_people(&self, query: &str, page: i32) -> Result<SearchHits<Person>, AppError> {
+        Ok(search_staff_api(query, page).await?.map(Person::from))
+    }
+}
+
+impl From<Anime> for Media {
+    fn from(anime: Anime) -> Self {
+        let title = anime.title.unwrap_or(AnimeTitle {
+            romaji: None,
+            english: None,
+            native: None,
+        });
+        Self {
+            id: anime.id,
+            provider: Provider::Anilist,
+            kind: MediaKind::Anime,
+            mal_id: anime.id_mal,
+            url: anime
+                .site_url
+                .unwrap_or_else(|| format!("https://anilist.co/anime/{}", anime.id)),
+            image_url: anime
+                .cover_image
+                .and_then(|cover| cover.extra_large.or(cover.large)),
+            titles: titles(title.romaji, title.english, title.native),
+            synonyms: anime.synonyms.unwrap_or_default(),
+            media_type: anime.format,
+            status: anime.status,
+            rating: anime
+                .is_adult
+                .filter(|adult| *adult)
+                .map(|_| ADULT_RATING.to_string()),
+            episodes: anime.episodes,
+            chapters: None,
+            volumes: None,
+            score: anime.average_score.map(|score| score as f32 / 10.0),
+            rank: None,
+            popularity: anime.popularity,
+            favorites: anime.favourites,
+            synopsis: anime.description,
+            season: anime.season.map(|season| season.to_lowercase()),
+            year: anime.season_year,
+            genres: anime.genres.unwrap_or_default(),
+            tags: anime
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|tag| tag.is_media_spoiler != Some(true))
+                .map(|tag| tag.name)
+                .collect(),
+            creators: anime
+                .studios
+                .and_then(|studios| studios.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|studio| studio.name)
+                .collect(),
+        }
+    }
+}
+
+impl From<Manga> for Media {
+    fn from(manga: Manga) -> Self {
+        let title = manga.title.unwrap_or(MangaTitle {
+            romaji: None,
+            english: None,
+            native: None,
+        });
+        Self {
+            id: manga.id,
+            provider: Provider::Anilist,
+            kind: MediaKind::Manga,
+            mal_id: manga.id_mal,
+            url: manga
+                .site_url
+                .unwrap_or_else(|| format!("https://anilist.co/manga/{}", manga.id)),
+            image_url: manga
+                .cover_image
+                .and_then(|cover| cover.extra_large.or(cover.large)),
+            titles: titles(title.romaji, title.english, title.native),
+            synonyms: manga.synonyms.unwrap_or_default(),
+            media_type: manga.format,
+            status: manga.status,
+            rating: manga
+                .is_adult
+                .filter(|adult| *adult)
+                .map(|_| ADULT_RATING.to_string()),
+            episodes: None,
+            chapters: manga.chapters,
+            volumes: manga.volumes,
+            score: manga.average_score.map(|score| score as f32 / 10.0),
+            rank: None,
+            popularity: manga.popularity,
+            favorites: manga.favourites,
+            synopsis: manga.description,
+            season: None,
+            year: manga.start_date.and_then(|date| date.year),
+            genres: manga.genres.unwrap_or_default(),
+            tags: manga
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|tag| tag.is_media_spoiler != Some(true))
+                .map(|tag| tag.name)
+                .collect(),
+            creators: manga
+                .staff
+                .and_then(|staff| staff.nodes)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|member| member.name.and_then(|name| name.full))
+                .collect(),
+        }
+    }
+}
+
+impl From<AnilistCharacter> for Character {
+    fn from(character: AnilistCharacter) -> Self {
+        let (name, name_native, nicknames) = character.name.map_or_else(
+            || (String::new(), None, Vec::default()),
+            |name| {
+                (
+                    name.full.unwrap_or_default(),
+                    name.native,
+                    name.alternative
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|alternative| !alternative.is_empty())
+                        .collect(),
+                )
+            },
+        );
+        Self {
+            id: character.id,
+            provider: Provider::Anilist,
+            url: character
+                .site_url
+                .unwrap_or_else(|| format!("https://anilist.co/character/{}", character.id)),
+            image_url: character.image.and_then(|image| image.large),
+            name,
+            name_native,
+            nicknames,
+            favorites: character.favourites,
+            about: character.description,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl From<Staff> for Person {
+    fn from(staff: Staff) -> Self {
+        let (name, name_native, alternate_names) = staff.name.map_or_else(
+            || (String::new(), None, Vec::default()),
+            |name| {
+                (
+                    name.full.unwrap_or_default(),
+                    name.native,
+                    name.alternative
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|alternative| !alternative.is_empty())
+                        .collect(),
+                )
+            },
+        );
+        Self {
+            id: staff.id,
+            provider: Provider::Anilist,
+            url: staff
+                .site_url
+                .unwrap_or_else(|| format!("https://anilist.co/staff/{}", staff.id)),
+            image_url: staff.image.and_then(|image| image.large),
+            name,
+            name_native,
+            alternate_names,
+            occupations: staff.primary_occupations.unwrap_or_default(),
+            favorites: staff.favourites,
+            about: staff.description,
+        }
+    }
+}
+
+fn titles(
+    romaji: Option<String>,
+    english: Option<String>,
+    native: Option<String>,
+) -> Vec<MediaTitle> {
+    [
+        ("Default", romaji),
+        ("English", english),
+        ("Japanese", native),
+    ]
+    .into_iter()
+    .filter_map(|(title_type, title)| {
+        title.map(|title| MediaTitle {
+            title_type: title_type.to_string(),
+            title,
+        })
+    })
+    .collect()
+}
@@ -0,0 +1,138 @@
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::{
+            apis::{
+                http::{post_json, replaying},
+                provider::SearchHits,
+            },
+            config::types::UTILS_CONFIG,
+            errors::error_template::AppError,
+        };
+        use governor::{
+            Quota, RateLimiter,
+            clock::DefaultClock,
+            state::{InMemoryState, NotKeyed},
+        };
+        use graphql_client::{GraphQLQuery, Response};
+        use once_cell::sync::Lazy;
+        use serde::{Deserialize, de::DeserializeOwned};
+        use std::{num::NonZeroU32, sync::Arc};
+
+        pub const ANILIST_URL: &str = "https://graphql.anilist.co/";
+        pub const ANILIST_PER_PAGE: i64 = 50;
+
+        fn anilist_url() -> &'static str {
+            UTILS_CONFIG
+                .get()
+                .map_or(ANILIST_URL, |config| config.apis.anilist_url.as_str())
+        }
+
+        pub static RATE_LIMITER_PER_MINUTE: Lazy<Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>> =
+            Lazy::new(|| {
+                let per_minute = UTILS_CONFIG.get().map_or(90, |config| config.apis.anilist_requests_per_minute);
+                let quota = Quota::per_minute(NonZeroU32::new(per_minute).unwrap_or(NonZeroU32::MIN));
+                Arc::new(RateLimiter::direct(quota))
+            });
+
+        #[derive(Deserialize)]
+        pub struct AnilistData<T> {
+            #[serde(rename = "Page")]
+            pub page: AnilistPage<T>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct AnilistPage<T> {
+            pub page_info: AnilistPageInfo,
+            #[serde(alias = "media", alias = "characters", alias = "staff")]
+            pub items: Vec<T>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct AnilistPageInfo {
+            pub current_page: Option<i64>,
+            pub has_next_page: Option<bool>,
+            pub total: Option<i64>,
+        }
+
+        pub async fn fetch_page<Q: GraphQLQuery, T: DeserializeOwned>(
+            variables: Q::Variables,
+        ) -> Result<AnilistPage<T>, AppError> {
+            if !replaying() {
+                RATE_LIMITER_PER_MINUTE.until_ready().await;
+            }
+            let resp = post_json(anilist_url(), &Q::build_query(variables)).await?;
+            let parsed = resp
+                .json::<Response<AnilistData<T>>>()
+                .map_err(|e| AppError::ProviderError(format!("AniList response: {e}")))?;
+            match (parsed.data, parsed.errors) {
+                (Some(data), _) => Ok(data.page),
+                (None, Some(errors)) => Err(AppError::ProviderError(format!(
+                    "AniList errors: {}",
+                    errors
+                        .iter()
+                        .map(|error| error.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))),
+                (None, None) => Err(AppError::ProviderError(
+                    "AniList returned no data".to_string(),
+                )),
+            }
+        }
+
+        pub async fn search_page<Q: GraphQLQuery, T: DeserializeOwned>(
+            variables: Q::Variables,
+        ) -> Result<SearchHits<T>, AppError> {
+            let page = fetch_page::<Q, T>(variables).await?;
+            Ok(SearchHits {
+                items: page.items,
+                has_next_page: page.page_info.has_next_page.unwrap_or(false),
+                total: page.page_info.total.and_then(|total| usize::try_from(total).ok()),
+            })
+        }
+
+        pub async fn all_pages<Q: GraphQLQuery, T: DeserializeOwned>(
+            variables: impl Fn(i64) -> Q::Variables,
+        ) -> Result<Vec<T>, AppError> {
+            let mut all_items = Vec::new();
+            let mut current_page = 1;
+            loop {
+                let page = fetch_page::<Q, T>(variables(current_page)).await.map_err(|e| {
+                    AppError::ProviderError(format!("AniList page {current_page} failed: {e}"))
+                })?;
+                all_items.extend(page.items);
+                if !page.page_info.has_next_page.unwrap_or(false) {
+                    break;
+                }
+                current_page += 1;
+            }
+
+            Ok(all_items)
+        }
+
+        pub async fn pages_until<Q: GraphQLQuery, T: DeserializeOwned>(
+            variables: impl Fn(i64) -> Q::Variables,
+            stop: impl Fn(&T) -> bool,
+        ) -> Result<Vec<T>, AppError> {
+            let mut items = Vec::new();
+            let mut current_page = 1;
+            loop {
+                let page = fetch_page::<Q, T>(variables(current_page)).await?;
+                let page_size = page.items.len();
+                let fresh: Vec<T> = page.items.into_iter().take_while(|item| !stop(item)).collect();
+                let exhausted = fresh.len() < page_size || !page.page_info.has_next_page.unwrap_or(false);
+                items.extend(fresh);
+                if exhausted {
+                    break;
+                }
+                current_page += 1;
+            }
+
+            Ok(items)
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::{
+    apis::{
+        anilist::{shared::search_page, staff::model::Staff},
+        provider::SearchHits,
+    },
+    errors::error_template::AppError,
+};
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/apis/anilist/schema.json",
+    query_path = "src/apis/anilist/staff/query.graphql"
+)]
+pub struct StaffSearchQuery;
+
+pub async fn search_staff_api(query: &str, page: i32) -> Result<SearchHits<Staff>, AppError> {
+    let variables = staff_search_query::Variables {
+        search: Some(query.to_owned()),
+        page: Some(page.into()),
+    };
+    search_page::<StaffSearchQuery, Staff>(variables).await
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Staff {
+    pub id: i32,
+    pub site_url: Option<String>,
+    pub name: Option<StaffName>,
+    pub image: Option<StaffImage>,
+    pub primary_occupations: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub favourites: Option<i32>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct StaffName {
+    pub first: Option<String>,
+    pub last: Option<String>,
+    pub full: Option<String>,
+    pub native: Option<String>,
+    pub alternative: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct StaffImage {
+    pub large: Option<String>,
+}
@@ -0,0 +1,135 @@
+use crate::{
+    config::{
+        settings::HttpMode,
+        types::{HTTP_CLIENT, UTILS_CONFIG},
+    },
+    errors::error_template::AppError,
+    utils::hash::fnv1a,
+};
+use http::{Method, StatusCode, header::RETRY_AFTER};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::{Path, PathBuf};
+use tokio::fs::{create_dir_all, read_to_string, write};
+
+#[derive(Deserialize, Serialize)]
+pub struct HttpResponse {
+    pub url: String,
+    pub status: u16,
+    pub retry_after: Option<String>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    #[must_use]
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.body)
+    }
+}
+
+/// Replayed responses are read from disk, so callers skip their provider rate limits
+#[must_use]
+pub fn replaying() -> bool {
+    UTILS_CONFIG
+        .get()
+        .is_some_and(|config| config.apis.http_mode == HttpMode::Replay)
+}
+
+pub async fn get(url: &str) -> Result<HttpResponse, AppError> {
+    execute(Method::GET, url, None).await
+}
+
+pub async fn post_json<B: Serialize>(url: &str, body: &B) -> Result<HttpResponse, AppError> {
+    let body = serde_json::to_string(body)
+        .map_err(|e| AppError::InternalError(format!("Encode request body: {e}")))?;
+    execute(Method::POST, url, Some(body)).await
+}
+
+async fn execute(
+    method: Method,
+    url: &str,
+    body: Option<String>,
+) -> Result<HttpResponse, AppError> {
+    let (mode, fixture_dir) = UTILS_CONFIG.get().map_or((HttpMode::Live, ""), |config| {
+        (config.apis.http_mode, config.apis.fixture_dir.as_str())
+    });
+    let fixture = fixture_path(fixture_dir, &method, url, body.as_deref());
+
+    if mode == HttpMode::Replay {
+        let recorded = read_to_string(&fixture).await.map_err(|e| {
+            AppError::InternalError(format!(
+                "No fixture for {method} {url} at {}: {e}",
+                fixture.display()
+            ))
+        })?;
+        return serde_json::from_str(&recorded).map_err(|e| {
+            AppError::InternalError(format!("Decode fixture {}: {e}", fixture.display()))
+        });
+    }
+
+    let mut request = HTTP_CLIENT.request(method, url);
+    if let Some(body) = body {
+        request = request
+            .header("Content-Type", "application/json")
+            .body(body);
+    }
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| AppError::ProviderError(format!("Request {url}: {e}")))?;
+    let status = resp.status().as_u16();
+    let retry_after = resp
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let body = resp
+        .text()
+        .await
+        .map_err(|e| AppError::ProviderError(format!("Response {url}: {e}")))?;
+    let response = HttpResponse {
+        url: url.to_string(),
+        status,
+        retry_after,
+        body,
+    };
+
+    if mode == HttpMode::Record {
+        record(&fixture, &response).await?;
+    }
+
+    Ok(response)
+}
+
+async fn record(fixture: &Path, response: &HttpResponse) -> Result<(), AppError> {
+    if let Some(dir) = fixture.parent() {
+        create_dir_all(dir)
+            .await
+            .map_err(|e| AppError::InternalError(format!("Create fixture dir: {e}")))?;
+    }
+    let serialized = serde_json::to_string_pretty(response)
+        .map_err(|e| AppError::InternalError(format!("Encode fixture: {e}")))?;
+    write(fixture, serialized)
+        .await
+        .map_err(|e| AppError::InternalError(format!("Write fixture {}: {e}", fixture.display())))
+}
+
+fn fixture_path(fixture_dir: &str, method: &Method, url: &str, body: Option<&str>) -> PathBuf {
+    let host = url
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or("unknown");
+    let hash = fnv1a(
+        [method.as_str(), url, body.unwrap_or_default()]
+            .iter()
+            .flat_map(|part| part.bytes().chain([0])),
+    );
+
+    PathBuf::from(fixture_dir)
+        .join(host)
+        .join(format!("{hash:016x}.json"))
+}
@@ -0,0 +1,31 @@
+use crate::apis::model::{CatalogIdentity, Entity, Provider};
+use cfg_if::cfg_if;
+use leptos::prelude::*;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::db::backend::PostgreSQLBackend;
+        use axum_login::AuthSession;
+    }
+}
+
+#[server(CanonicalId, "/api", "Url", "canonical_id")]
+pub async fn canonical_id(
+    provider: Provider,
+    entity: Entity,
+    id: i32,
+) -> Result<Option<CatalogIdentity>, ServerFnError> {
+    let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+    let Some(canonical_id) = session.backend.canonical_id(provider, entity, id).await? else {
+        return Ok(None);
+    };
+
+    Ok(session.backend.catalog_identity(canonical_id).await?)
+}
+
+#[server(CatalogIdentityById, "/api", "Url", "catalog_identity")]
+pub async fn catalog_identity(canonical_id: i64) -> Result<Option<CatalogIdentity>, ServerFnError> {
+    let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+
+    Ok(session.backend.catalog_identity(canonical_id).await?)
+}
@@ -0,0 +1,447 @@
+use crate::{
+    apis::model::{
+        CanonicalMedia, CatalogIdentity, Character, Entity, Media, MediaKind, Provider,
+        canonical_index_name, index_name,
+    },
+    config::{
+        consts::{INDEXER_BATCH_SIZE, REINDEX_TASK_TIMEOUT_SECONDS},
+        types::{INDEX_LOCKS, MEILISEARCH_CLIENT},
+    },
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+    utils::normalize::search_forms,
+};
+use meilisearch_sdk::{
+    client::{Client, SwapIndexes},
+    settings::{MinWordSizeForTypos, Settings, TypoToleranceSettings},
+    task_info::TaskInfo,
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::Duration,
+};
+use tracing::info;
+
+const MEDIA_SEARCHABLE: [&str; 7] = [
+    "title_romaji",
+    "title_english",
+    "title_native",
+    "synonyms",
+    "title_search",
+    "titles.title",
+    "synopsis",
+];
+const MEDIA_FILTERABLE: [&str; 11] = [
+    "provider",
+    "sources.provider",
+    "genres",
+    "tags",
+    "year",
+    "season",
+    "status",
+    "type",
+    "score",
+    "popularity",
+    "rating",
+];
+const MEDIA_SORTABLE: [&str; 4] = ["score", "popularity", "favorites", "year"];
+const CHARACTER_SEARCHABLE: [&str; 5] =
+    ["name", "name_native", "nicknames", "name_search", "about"];
+const CHARACTER_FILTERABLE: [&str; 1] = ["provider"];
+const CHARACTER_SORTABLE: [&str; 1] = ["favorites"];
+const RANKING_RULES: [&str; 7] = [
+    "words",
+    "typo",
+    "proximity",
+    "attribute",
+    "sort",
+    "exactness",
+    "favorites:desc",
+];
+const ROMAJI_TITLE: &str = "Default";
+const ENGLISH_TITLE: &str = "English";
+const NATIVE_TITLE: &str = "Japanese";
+const STOP_WORDS: [&str; 9] = ["the", "a", "an", "of", "and", "in", "on", "to", "with"];
+
+/// Canonical media as stored in Meilisearch, with each title language in its own attribute
+/// and the normalized forms from [`search_forms`] for kana and romanization variants
+#[derive(Serialize)]
+struct MediaDocument<'a> {
+    #[serde(flatten)]
+    media: &'a CanonicalMedia,
+    title_romaji: Vec<&'a str>,
+    title_english: Vec<&'a str>,
+    title_native: Vec<&'a str>,
+    title_search: BTreeSet<String>,
+}
+
+impl<'a> MediaDocument<'a> {
+    fn new(media: &'a CanonicalMedia) -> Self {
+        let titles = |title_type: &str| {
+            media
+                .titles
+                .iter()
+                .filter(|title| title.title_type == title_type)
+                .map(|title| title.title.as_str())
+                .collect()
+        };
+        // Kana queries carry no word breaks, so keep an unspaced form next to the spaced one
+        let title_search = media
+            .titles
+            .iter()
+            .map(|title| title.title.as_str())
+            .chain(media.synonyms.iter().map(String::as_str))
+            .flat_map(search_forms)
+            .flat_map(|normalized| [normalized.replace(' ', ""), normalized])
+            .collect();
+
+        Self {
+            media,
+            title_romaji: titles(ROMAJI_TITLE),
+            title_english: titles(ENGLISH_TITLE),
+            title_native: titles(NATIVE_TITLE),
+            title_search,
+        }
+    }
+}
+
+fn media_documents(media: &[CanonicalMedia]) -> Vec<MediaDocument<'_>> {
+    media.iter().map(MediaDocument::new).collect()
+}
+
+/// A character as stored in Meilisearch, with the [`search_forms`] of its names so kana
+/// and romaji queries find each other
+#[derive(Serialize)]
+struct CharacterDocument<'a> {
+    #[serde(flatten)]
+    character: &'a Character,
+    name_search: BTreeSet<String>,
+}
+
+impl<'a> CharacterDocument<'a> {
+    fn new(character: &'a Character) -> Self {
+        let name_search = [character.name.as_str()]
+            .into_iter()
+            .chain(character.name_native.as_deref())
+            .chain(character.nicknames.iter().map(String::as_str))
+            .flat_map(search_forms)
+            .flat_map(|normalized| [normalized.replace(' ', ""), normalized])
+            .collect();
+
+        Self {
+            character,
+            name_search,
+        }
+    }
+}
+
+fn character_documents(characters: &[Character]) -> Vec<CharacterDocument<'_>> {
+    characters.iter().map(CharacterDocument::new).collect()
+}
+
+pub async fn configure_indexes() -> Result<(), AppError> {
+    for entity in Entity::ALL {
+        for provider in Provider::ALL {
+            configure_index(&index_name(provider, entity), entity).await?;
+        }
+        if entity.media_kind().is_some() {
+            configure_index(canonical_index_name(entity), entity).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn configure_index(index: &str, entity: Entity) -> Result<TaskInfo, AppError> {
+    let (searchable, filterable, sortable) = match entity.media_kind() {
+        Some(_) => (
+            &MEDIA_SEARCHABLE[..],
+            &MEDIA_FILTERABLE[..],
+            &MEDIA_SORTABLE[..],
+        ),
+        None => (
+            &CHARACTER_SEARCHABLE[..],
+            &CHARACTER_FILTERABLE[..],
+            &CHARACTER_SORTABLE[..],
+        ),
+    };
+    let settings = Settings::new()
+        .with_searchable_attributes(searchable)
+        .with_filterable_attributes(filterable)
+        .with_sortable_attributes(sortable)
+        .with_ranking_rules(RANKING_RULES)
+        .with_stop_words(STOP_WORDS)
+        .with_typo_tolerance(TypoToleranceSettings {
+            enabled: Some(true),
+            disable_on_attributes: None,
+            disable_on_words: None,
+            min_word_size_for_typos: Some(MinWordSizeForTypos {
+                one_typo: Some(4),
+                two_typos: Some(8),
+            }),
+        });
+
+    meilisearch()?
+        .index(index)
+        .set_settings(&settings)
+        .await
+        .map_err(|e| AppError::SearchError(format!("Configure {index}: {e}")))
+}
+
+pub async fn index_records(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    ids: &[i32],
+) -> Result<(), AppError> {
+    let client = meilisearch()?;
+    match entity.media_kind() {
+        Some(kind) => {
+            let media = backend.load_media(provider, kind, ids).await?;
+            let live = index_name(provider, entity);
+            {
+                let _lock = INDEX_LOCKS[live.as_str()].lock().await;
+                push(client, &live, &media).await?;
+            }
+            let identities = backend.linked_identities(provider, entity, ids).await?;
+            let merged = merge_identities(backend, kind, identities).await?;
+            let canonical = canonical_index_name(entity);
+            let _lock = INDEX_LOCKS[canonical].lock().await;
+            push(client, canonical, &media_documents(&merged)).await?;
+        }
+        None => {
+            let characters = backend.load_characters(provider, ids).await?;
+            let live = index_name(provider, entity);
+            let _lock = INDEX_LOCKS[live.as_str()].lock().await;
+            push(client, &live, &character_documents(&characters)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn reindex(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+) -> Result<usize, AppError> {
+    let indexed = reindex_provider(backend, provider, entity).await?;
+    if let Some(kind) = entity.media_kind() {
+        reindex_canonical(backend, kind).await?;
+    }
+
+    Ok(indexed)
+}
+
+async fn reindex_provider(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+) -> Result<usize, AppError> {
+    let client = meilisearch()?;
+    let live = index_name(provider, entity);
+    let _lock = INDEX_LOCKS[live.as_str()].lock().await;
+    let temp = prepare_temp(&live, entity).await?;
+
+    let mut tasks = Vec::new();
+    let mut indexed = 0;
+    let mut after = i32::MIN;
+    loop {
+        let ids = backend
+            .record_ids_after(provider, entity, after, INDEXER_BATCH_SIZE)
+            .await?;
+        let Some(&last) = ids.last() else {
+            break;
+        };
+        let task = match entity.media_kind() {
+            Some(kind) => {
+                push(
+                    client,
+                    &temp,
+                    &backend.load_media(provider, kind, &ids).await?,
+                )
+                .await?
+            }
+            None => {
+                push(
+                    client,
+                    &temp,
+                    &character_documents(&backend.load_characters(provider, &ids).await?),
+                )
+                .await?
+            }
+        };
+        tasks.extend(task);
+        indexed += ids.len();
+        after = last;
+    }
+    swap_in(&live, &temp, tasks, indexed).await?;
+    info!("Reindexed {live} from the database: {indexed} documents");
+
+    Ok(indexed)
+}
+
+async fn reindex_canonical(
+    backend: &PostgreSQLBackend,
+    kind: MediaKind,
+) -> Result<usize, AppError> {
+    let client = meilisearch()?;
+    let entity = Entity::from(kind);
+    let live = canonical_index_name(entity);
+    let _lock = INDEX_LOCKS[live].lock().await;
+    let temp = prepare_temp(live, entity).await?;
+
+    let mut tasks = Vec::new();
+    let mut indexed = 0;
+    let mut after = i64::MIN;
+    loop {
+        let identities = backend
+            .identities_after(entity, after, INDEXER_BATCH_SIZE)
+            .await?;
+        let Some(last) = identities.last().map(|identity| identity.canonical_id) else {
+            break;
+        };
+        let merged = merge_identities(backend, kind, identities).await?;
+        tasks.extend(push(client, &temp, &media_documents(&merged)).await?);
+        indexed += merged.len();
+        after = last;
+    }
+    swap_in(live, &temp, tasks, indexed).await?;
+    info!("Reindexed {live} from the database: {indexed} documents");
+
+    Ok(indexed)
+}
+
+async fn prepare_temp(live: &str, entity: Entity) -> Result<String, AppError> {
+    let client = meilisearch()?;
+    let temp = format!("{live}_reindex");
+    if client.get_index(&temp).await.is_ok() {
+        wait(client.index(&temp).delete().await).await?;
+    }
+    wait(client.create_index(&temp, Some("id")).await).await?;
+    wait(configure_index(&temp, entity).await).await?;
+
+    Ok(temp)
+}
+
+async fn swap_in(
+    live: &str,
+    temp: &str,
+    tasks: Vec<TaskInfo>,
+    expected: usize,
+) -> Result<(), AppError> {
+    let client = meilisearch()?;
+    for task in tasks {
+        wait(Ok(task)).await?;
+    }
+
+    let stats = client
+        .index(temp)
+        .get_stats()
+        .await
+        .map_err(|e| AppError::SearchError(format!("Stats of {temp}: {e}")))?;
+    if stats.number_of_documents != expected {
+        return Err(AppError::SearchError(format!(
+            "Reindex of {live} produced {} documents, expected {expected}; keeping the live index",
+            stats.number_of_documents
+        )));
+    }
+
+    if client.get_index(live).await.is_err() {
+        wait(client.create_index(live, Some("id")).await).await?;
+    }
+    wait(
+        client
+            .swap_indexes([&SwapIndexes {
+                indexes: (live.to_string(), temp.to_string()),
+            }])
+            .await,
+    )
+    .await?;
+    wait(client.index(temp).delete().await).await
+}
+
+async fn wait(task: Result<TaskInfo, meilisearch_sdk::errors::Error>) -> Result<(), AppError> {
+    let task = task
+        .map_err(|e| AppError::SearchError(format!("Enqueue task: {e}")))?
+        .wait_for_completion(
+            meilisearch()?,
+            None,
+            Some(Duration::from_secs(REINDEX_TASK_TIMEOUT_SECONDS)),
+        )
+        .await
+        .map_err(|e| AppError::SearchError(format!("Wait for task: {e}")))?;
+    if task.is_failure() {
+        return Err(AppError::SearchError(format!(
+            "Task failed: {}",
+            task.unwrap_failure()
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn merge_identities(
+    backend: &PostgreSQLBackend,
+    kind: MediaKind,
+    identities: Vec<CatalogIdentity>,
+) -> Result<Vec<CanonicalMedia>, AppError> {
+    let mut records = Vec::new();
+    for (provider, ids) in linked_ids(&identities) {
+        records.extend(backend.load_media(provider, kind, &ids).await?);
+    }
+
+    Ok(merge_records(identities, records))
+}
+
+#[must_use]
+pub fn linked_ids(identities: &[CatalogIdentity]) -> HashMap<Provider, Vec<i32>> {
+    let mut linked: HashMap<Provider, Vec<i32>> = HashMap::new();
+    for id in identities.iter().flat_map(|identity| &identity.ids) {
+        linked.entry(id.provider).or_default().push(id.id);
+    }
+    linked
+}
+
+#[must_use]
+pub fn merge_records(identities: Vec<CatalogIdentity>, records: Vec<Media>) -> Vec<CanonicalMedia> {
+    let records: HashMap<_, _> = records
+        .into_iter()
+        .map(|media| ((media.provider, media.id), media))
+        .collect();
+    identities
+        .into_iter()
+        .filter_map(|identity| {
+            let sources: Vec<_> = identity
+                .ids
+                .iter()
+                .filter_map(|id| records.get(&(id.provider, id.id)).cloned())
+                .collect();
+            CanonicalMedia::merge(identity.canonical_id, &sources)
+        })
+        .collect()
+}
+
+async fn push<T: Serialize + Send + Sync>(
+    client: &Client,
+    index: &str,
+    documents: &[T],
+) -> Result<Option<TaskInfo>, AppError> {
+    if documents.is_empty() {
+        return Ok(None);
+    }
+    client
+        .index(index)
+        .add_or_replace(documents, Some("id"))
+        .await
+        .map(Some)
+        .map_err(|e| AppError::SearchError(format!("Push documents to {index}: {e}")))
+}
+
+fn meilisearch() -> Result<&'static Client, AppError> {
+    MEILISEARCH_CLIENT
+        .get()
+        .map(AsRef::as_ref)
+        .ok_or_else(|| AppError::SearchError("Meilisearch client not initialized".to_string()))
+}
@@ -1,92 +1,35 @@
-use crate::apis::jikan::anime::model::Anime;
-use leptos::prelude::*;
+use crate::{
+    apis::{
+        jikan::{
+            anime::model::Anime,
+            shared::{all_pages, by_id, catalog_page, pages_until, search},
+        },
+        provider::{CatalogPage, SearchHits},
+    },
+    errors::error_template::AppError,
+};
 
-#[server]
-pub async fn search_anime(query: String) -> Result<Vec<Anime>, ServerFnError> {
-    use crate::config::types::MEILISEARCH_CLIENT;
-    if let Some(meilisearch_client) = MEILISEARCH_CLIENT.get()
-        && let Ok(index) = meilisearch_client.get_index("jikan_anime").await
-        && let Ok(results) = index
-            .search()
-            .with_query(query.as_str())
-            .with_attributes_to_search_on(&["titles.title"])
-            .execute::<Anime>()
-            .await
-    {
-        Ok(results.hits.into_iter().map(|s| s.result).collect())
-    } else {
-        search_anime_api(query).await
-    }
+pub async fn search_anime_api(query: &str, page: i32) -> Result<SearchHits<Anime>, AppError> {
+    search("anime", query, page).await
 }
 
-#[server]
-pub async fn search_anime_api(query: String) -> Result<Vec<Anime>, ServerFnError> {
-    use crate::{
-        apis::jikan::{
-            anime::model::AnimeResponse,
-            shared::{RATE_LIMITER_PER_MINUTE, RATE_LIMITER_PER_SECOND},
-        },
-        config::types::HTTP_CLIENT,
-    };
-    let request_url = format!("https://api.jikan.moe/v4/anime?q={query}");
-    RATE_LIMITER_PER_SECOND.until_ready().await;
-    RATE_LIMITER_PER_MINUTE.until_ready().await;
-    match HTTP_CLIENT.get(request_url).send().await {
-        Ok(resp) => match resp.json::<AnimeResponse>().await {
-            Ok(data) if !data.data.is_empty() => Ok(data.data),
-            Ok(_) | Err(_) => Ok(Vec::default()),
-        },
-        Err(_) => Ok(Vec::default()),
-    }
+pub async fn anime_by_id(id: i32) -> Result<Option<Anime>, AppError> {
+    by_id("anime", id).await
 }
 
-#[server]
-pub async fn all_anime() -> Result<Vec<Anime>, ServerFnError> {
-    use crate::{
-        apis::jikan::{
-            anime::model::AnimeResponse,
-            shared::{RATE_LIMITER_PER_MINUTE, RATE_LIMITER_PER_SECOND},
-        },
-        config::types::HTTP_CLIENT,
-    };
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
-
-    let initial_resp = HTTP_CLIENT
-        .get("https://api.jikan.moe/v4/anime")
-        .send()
-        .await?;
-
-    let initial_data: AnimeResponse = initial_resp.json().await?;
-    let total_pages = initial_data.pagination.last_visible_page;
-
-    let semaphore = Arc::new(Semaphore::new(3));
-
-    let fetch_page = move |page: i32| {
-        let semaphore_clone = semaphore.clone();
-        async move {
-            let _permit = semaphore_clone.acquire().await.unwrap();
-
-            RATE_LIMITER_PER_SECOND.until_ready().await;
-            RATE_LIMITER_PER_MINUTE.until_ready().await;
-
-            let request_url = format!("https://api.jikan.moe/v4/anime?page={page}");
-
-            match HTTP_CLIENT.get(&request_url).send().await {
-                Ok(resp) => resp
-                    .json::<AnimeResponse>()
-                    .await
-                    .map(|data| data.data)
-                    .ok(),
-                Err(_) => None,
-            }
-        }
-    };
+pub async fn anime_page(page: i32) -> Result<CatalogPage<Anime>, AppError> {
+    catalog_page("anime", page).await
+}
 
-    let page_futures: Vec<_> = (1..=total_pages).map(fetch_page).collect();
-    let results = join_all(page_futures).await;
-    let all_anime: Vec<Anime> = results.into_iter().flatten().flatten().collect();
+pub async fn ongoing_anime() -> Result<Vec<Anime>, AppError> {
+    let mut ongoing = all_pages("anime?status=airing").await?;
+    ongoing.extend(all_pages("anime?status=upcoming").await?);
+    Ok(ongoing)
+}
 
-    Ok(all_anime)
+pub async fn newest_anime(known_id: Option<i32>) -> Result<Vec<Anime>, AppError> {
+    pages_until("anime?order_by=mal_id&sort=desc", |anime: &Anime| {
+        Some(anime.mal_id) <= known_id
+    })
+    .await
 }
@@ -1,24 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeResponse {
-    pub pagination: AnimePagination,
-    pub data: Vec<Anime>,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimePagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: AnimeItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct AnimeItems {
-    pub count: i32,
-    pub total: usize,
-}
-
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Anime {
     pub mal_id: i32,
@@ -39,6 +20,7 @@ pub struct Anime {
     pub score: Option<f32>,
     pub rank: Option<i32>,
     pub popularity: Option<i32>,
+    pub members: Option<i32>,
     pub favorites: Option<i32>,
     pub synopsis: Option<String>,
     pub background: Option<String>,
@@ -46,6 +28,10 @@ pub struct Anime {
     pub year: Option<i32>,
     pub broadcast: AnimeBroadcast,
     pub genres: Vec<AnimeGenres>,
+    #[serde(default)]
+    pub themes: Vec<AnimeGenres>,
+    #[serde(default)]
+    pub demographics: Vec<AnimeGenres>,
     pub studios: Vec<AnimeStudios>,
 }
 
@@ -1,91 +1,33 @@
-use crate::apis::jikan::character::model::Character;
-use leptos::prelude::*;
-
-#[server]
-pub async fn search_character(query: String) -> Result<Vec<Character>, ServerFnError> {
-    use crate::config::types::MEILISEARCH_CLIENT;
-    if let Some(meilisearch_client) = MEILISEARCH_CLIENT.get()
-        && let Ok(index) = meilisearch_client.get_index("jikan_character").await
-        && let Ok(results) = index
-            .search()
-            .with_query(query.as_str())
-            .execute::<Character>()
-            .await
-    {
-        Ok(results.hits.into_iter().map(|s| s.result).collect())
-    } else {
-        search_character_api(query).await
-    }
-}
-
-#[server]
-pub async fn search_character_api(query: String) -> Result<Vec<Character>, ServerFnError> {
-    use crate::{
-        apis::jikan::{
-            character::model::CharacterResponse,
-            shared::{RATE_LIMITER_PER_MINUTE, RATE_LIMITER_PER_SECOND},
-        },
-        config::types::HTTP_CLIENT,
-    };
-    let request_url = format!("https://api.jikan.moe/v4/character?q={query}");
-    RATE_LIMITER_PER_SECOND.until_ready().await;
-    RATE_LIMITER_PER_MINUTE.until_ready().await;
-    match HTTP_CLIENT.get(request_url).send().await {
-        Ok(resp) => match resp.json::<CharacterResponse>().await {
-            Ok(data) if !data.data.is_empty() => Ok(data.data),
-            Ok(_) | Err(_) => Ok(Vec::default()),
+use crate::{
+    apis::{
+        jikan::{
+            character::model::Character,
+            shared::{by_id, catalog_page, pages_until, search},
         },
-        Err(_) => Ok(Vec::default()),
-    }
+        provider::{CatalogPage, SearchHits},
+    },
+    errors::error_template::AppError,
+};
+
+pub async fn search_character_api(
+    query: &str,
+    page: i32,
+) -> Result<SearchHits<Character>, AppError> {
+    search("characters", query, page).await
 }
 
-#[server]
-pub async fn all_character() -> Result<Vec<Character>, ServerFnError> {
-    use crate::{
-        apis::jikan::{
-            character::model::CharacterResponse,
-            shared::{RATE_LIMITER_PER_MINUTE, RATE_LIMITER_PER_SECOND},
-        },
-        config::types::HTTP_CLIENT,
-    };
-    use futures::future::join_all;
-    use std::sync::Arc;
-    use tokio::sync::Semaphore;
-
-    let initial_resp = HTTP_CLIENT
-        .get("https://api.jikan.moe/v4/characters")
-        .send()
-        .await?;
-
-    let initial_data: CharacterResponse = initial_resp.json().await?;
-    let total_pages = initial_data.pagination.last_visible_page;
-
-    let semaphore = Arc::new(Semaphore::new(3));
-
-    let fetch_page = move |page: i32| {
-        let semaphore_clone = semaphore.clone();
-        async move {
-            let _permit = semaphore_clone.acquire().await.unwrap();
-
-            RATE_LIMITER_PER_SECOND.until_ready().await;
-            RATE_LIMITER_PER_MINUTE.until_ready().await;
-
-            let request_url = format!("https://api.jikan.moe/v4/characters?page={page}");
-
-            match HTTP_CLIENT.get(&request_url).send().await {
-                Ok(resp) => resp
-                    .json::<CharacterResponse>()
-                    .await
-                    .map(|data| data.data)
-                    .ok(),
-                Err(_) => None,
-            }
-        }
-    };
+pub async fn character_by_id(id: i32) -> Result<Option<Character>, AppError> {
+    by_id("characters", id).await
+}
 
-    let page_futures: Vec<_> = (1..=total_pages).map(fetch_page).collect();
-    let results = join_all(page_futures).await;
-    let all_character: Vec<Character> = results.into_iter().flatten().flatten().collect();
+pub async fn character_page(page: i32) -> Result<CatalogPage<Character>, AppError> {
+    catalog_page("characters", page).await
+}
 
-    Ok(all_character)
+pub async fn newest_character(known_id: Option<i32>) -> Result<Vec<Character>, AppError> {
+    pages_until(
+        "characters?order_by=mal_id&sort=desc",
+        |character: &Character| Some(character.mal_id) <= known_id,
+    )
+    .await
 }
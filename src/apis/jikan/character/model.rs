@@ -1,24 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterResponse {
-    pub pagination: CharacterPagination,
-    pub data: Vec<Character>,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterPagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: CharacterItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct CharacterItems {
-    pub count: i32,
-    pub total: usize,
-}
-
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Character {
     pub mal_id: i32,
@@ -1,24 +1,5 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct MangaResponse {
-    pub data: Vec<Manga>,
-    pub pagination: MangaPagination,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct MangaPagination {
-    pub last_visible_page: i32,
-    pub has_next_page: bool,
-    pub items: MangaItems,
-}
-
-#[derive(Deserialize, Serialize, Clone)]
-pub struct MangaItems {
-    pub count: i32,
-    pub total: usize,
-}
-
 #[derive(Deserialize, Serialize, Clone)]
 pub struct Manga {
     pub mal_id: i32,
@@ -36,10 +17,15 @@ pub struct Manga {
     pub score: Option<f32>,
     pub rank: Option<i32>,
     pub popularity: Option<i32>,
+    pub members: Option<i32>,
     pub favorites: Option<i32>,
     pub synopsis: Option<String>,
     pub background: Option<String>,
     pub genres: Vec<MangaGenres>,
+    #[serde(default)]
+    pub themes: Vec<MangaGenres>,
+    #[serde(default)]
+    pub demographics: Vec<MangaGenres>,
     pub authors: Vec<MangaAuthors>,
 }
 
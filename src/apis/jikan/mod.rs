@@ -1,5 +1,13 @@
+use cfg_if::cfg_if;
+
 pub mod anime;
 pub mod character;
 pub mod manga;
+pub mod people;
 pub mod shared;
-pub mod tracker;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod provider;
+    }
+}
@@ -0,0 +1,11 @@
+use crate::{
+    apis::{
+        jikan::{people::model::Person, shared::search},
+        provider::SearchHits,
+    },
+    errors::error_template::AppError,
+};
+
+pub async fn search_people_api(query: &str, page: i32) -> Result<SearchHits<Person>, AppError> {
+    search("people", query, page).await
+}
@@ -0,0 +1,9 @@
+use cfg_if::cfg_if;
+
+pub mod model;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod fetch;
+    }
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Person {
+    pub mal_id: i32,
+    pub url: String,
+    pub images: PersonImageTypes,
+    pub name: String,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    #[serde(default)]
+    pub alternate_names: Vec<String>,
+    pub favorites: i32,
+    pub about: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct PersonImageTypes {
+    pub jpg: PersonImageJpg,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct PersonImageJpg {
+    pub image_url: Option<String>,
+}
@@ -0,0 +1,250 @@
+use crate::{
+    apis::{
+        jikan::{
+            anime::{fetch::*, model::Anime},
+            character::{fetch::*, model::Character as JikanCharacter},
+            manga::{fetch::*, model::Manga},
+            people::{fetch::*, model::Person as JikanPerson},
+        },
+        model::{Character, Media, MediaKind, MediaTitle, Person, Provider},
+        provider::{CatalogPage, MetadataProvider, SearchHits, SyncCursor},
+    },
+    errors::error_template::AppError,
+};
+use async_trait::async_trait;
+
+pub struct JikanProvider;
+
+#[async_trait]
+impl MetadataProvider for JikanProvider {
+    fn provider(&self) -> Provider {
+        Provider::Jikan
+    }
+
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => search_anime_api(query, page).await?.map(Media::from),
+            MediaKind::Manga => search_manga_api(query, page).await?.map(Media::from),
+        })
+    }
+
+    async fn media_by_id(&self, kind: MediaKind, id: i32) -> Result<Option<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => anime_by_id(id).await?.map(Media::from),
+            MediaKind::Manga => manga_by_id(id).await?.map(Media::from),
+        })
+    }
+
+    async fn media_page(&self, kind: MediaKind, page: i32) -> Result<CatalogPage<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => {
+                let page = anime_page(page).await?;
+                CatalogPage {
+                    items: page.items.into_iter().map(Media::from).collect(),
+                    has_next_page: page.has_next_page,
+                }
+            }
+            MediaKind::Manga => {
+                let page = manga_page(page).await?;
+                CatalogPage {
+                    items: page.items.into_iter().map(Media::from).collect(),
+                    has_next_page: page.has_next_page,
+                }
+            }
+        })
+    }
+
+    async fn updated_media(
+        &self,
+        kind: MediaKind,
+        cursor: SyncCursor,
+    ) -> Result<Vec<Media>, AppError> {
+        Ok(match kind {
+            MediaKind::Anime => ongoing_anime()
+                .await?
+                .into_iter()
+                .chain(newest_anime(cursor.known_id).await?)
+                .map(Media::from)
+                .collect(),
+            MediaKind::Manga => ongoing_manga()
+                .await?
+                .into_iter()
+                .chain(newest_manga(cursor.known_id).await?)
+                .map(Media::from)
+                .collect(),
+        })
+    }
+
+    async fn search_characters(
+        &self,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError> {
+        Ok(search_character_api(query, page)
+            .await?
+            .map(Character::from))
+    }
+
+    async fn character_by_id(&self, id: i32) -> Result<Option<Character>, AppError> {
+        Ok(character_by_id(id).await?.map(Character::from))
+    }
+
+    async fn character_page(&self, page: i32) -> Result<CatalogPage<Character>, AppError> {
+        let page = character_page(page).await?;
+        Ok(CatalogPage {
+            items: page.items.into_iter().map(Character::from).collect(),
+            has_next_page: page.has_next_page,
+        })
+    }
+
+    async fn updated_characters(&self, cursor: SyncCursor) -> Result<Vec<Character>, AppError> {
+        Ok(newest_character(cursor.known_id)
+            .await?
+            .into_iter()
+            .map(Character::from)
+            .collect())
+    }
+
+    async fn search_people(&self, query: &str, page: i32) -> Result<SearchHits<Person>, AppError> {
+        Ok(search_people_api(query, page).await?.map(Person::from))
+    }
+}
+
+impl From<Anime> for Media {
+    fn from(anime: Anime) -> Self {
+        let (synonyms, titles): (Vec<MediaTitle>, Vec<MediaTitle>) = anime
+            .titles
+            .into_iter()
+            .map(|title| MediaTitle {
+                title_type: title.title_type,
+                title: title.title,
+            })
+            .partition(|title| title.title_type == "Synonym");
+        Self {
+            id: anime.mal_id,
+            provider: Provider::Jikan,
+            kind: MediaKind::Anime,
+            mal_id: Some(anime.mal_id),
+            url: anime.url,
+            image_url: anime.images.webp.image_url,
+            titles,
+            synonyms: synonyms.into_iter().map(|synonym| synonym.title).collect(),
+            media_type: anime.anime_type,
+            status: anime.status,
+            rating: anime.rating,
+            episodes: anime.episodes,
+            chapters: None,
+            volumes: None,
+            score: anime.score,
+            rank: anime.rank,
+            popularity: anime.members,
+            favorites: anime.favorites,
+            synopsis: anime.synopsis,
+            season: anime.season,
+            year: anime.year,
+            genres: anime.genres.into_iter().map(|genre| genre.name).collect(),
+            tags: anime
+                .themes
+                .into_iter()
+                .chain(anime.demographics)
+                .map(|theme| theme.name)
+                .collect(),
+            creators: anime
+                .studios
+                .into_iter()
+                .map(|studio| studio.name)
+                .collect(),
+        }
+    }
+}
+
+impl From<Manga> for Media {
+    fn from(manga: Manga) -> Self {
+        let (synonyms, titles): (Vec<MediaTitle>, Vec<MediaTitle>) = manga
+            .titles
+            .into_iter()
+            .map(|title| MediaTitle {
+                title_type: title.title_type,
+                title: title.title,
+            })
+            .partition(|title| title.title_type == "Synonym");
+        Self {
+            id: manga.mal_id,
+            provider: Provider::Jikan,
+            kind: MediaKind::Manga,
+            mal_id: Some(manga.mal_id),
+            url: manga.url,
+            image_url: manga.images.webp.image_url,
+            titles,
+            synonyms: synonyms.into_iter().map(|synonym| synonym.title).collect(),
+            media_type: manga.manga_type,
+            status: Some(manga.status),
+            rating: None,
+            episodes: None,
+            chapters: manga.chapters,
+            volumes: manga.volumes,
+            score: manga.score,
+            rank: manga.rank,
+            popularity: manga.members,
+            favorites: manga.favorites,
+            synopsis: manga.synopsis,
+            season: None,
+            year: None,
+            genres: manga.genres.into_iter().map(|genre| genre.name).collect(),
+            tags: manga
+                .themes
+                .into_iter()
+                .chain(manga.demographics)
+                .map(|theme| theme.name)
+                .collect(),
+            creators: manga
+                .authors
+                .into_iter()
+                .map(|author| author.name)
+                .collect(),
+        }
+    }
+}
+
+impl From<JikanCharacter> for Character {
+    fn from(character: JikanCharacter) -> Self {
+        Self {
+            id: character.mal_id,
+            provider: Provider::Jikan,
+            url: character.url,
+            image_url: character.images.webp.image_url,
+            name: character.name,
+            name_native: character.name_kanji,
+            nicknames: character.nicknames,
+            favorites: Some(character.favorites),
+            about: character.about,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+impl From<JikanPerson> for Person {
+    fn from(person: JikanPerson) -> Self {
+        let name_native = match (person.family_name, person.given_name) {
+            (Some(family_name), Some(given_name)) => Some(format!("{family_name}{given_name}")),
+            (family_name, given_name) => family_name.or(given_name),
+        };
+        Self {
+            id: person.mal_id,
+            provider: Provider::Jikan,
+            url: person.url,
+            image_url: person.images.jpg.image_url,
+            name: person.name,
+            name_native,
+            alternate_names: person.alternate_names,
+            occupations: Vec::default(),
+            favorites: Some(person.favorites),
+            about: person.about,
+        }
+    }
+}
@@ -2,24 +2,245 @@ use cfg_if::cfg_if;
 
 cfg_if! {
     if #[cfg(feature = "ssr")] {
+        use crate::{
+            apis::{
+                http::{get, replaying},
+                provider::{CatalogPage, SearchHits},
+            },
+            config::{
+                consts::SEARCH_PAGE_SIZE,
+                types::{RNG, UTILS_CONFIG},
+            },
+            errors::error_template::AppError,
+        };
+        use futures::future::join_all;
         use governor::{
             Quota, RateLimiter,
             clock::DefaultClock,
             state::{InMemoryState, NotKeyed},
         };
+        use http::StatusCode;
         use once_cell::sync::Lazy;
+        use serde::{Deserialize, de::DeserializeOwned};
         use std::{num::NonZeroU32, sync::Arc};
+        use tokio::{
+            sync::Semaphore,
+            time::{Duration, sleep},
+        };
+        use tracing::warn;
+        use urlencoding::encode;
+
+        pub const JIKAN_BASE_URL: &str = "https://api.jikan.moe/v4";
+
+        fn base_url() -> &'static str {
+            UTILS_CONFIG
+                .get()
+                .map_or(JIKAN_BASE_URL, |config| config.apis.jikan_base_url.trim_end_matches('/'))
+        }
 
         pub static RATE_LIMITER_PER_SECOND: Lazy<Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>> =
             Lazy::new(|| {
-                let quota = Quota::per_second(NonZeroU32::new(3).unwrap());
+                let per_second = UTILS_CONFIG.get().map_or(3, |config| config.apis.jikan_requests_per_second);
+                let quota = Quota::per_second(NonZeroU32::new(per_second).unwrap_or(NonZeroU32::MIN));
                 Arc::new(RateLimiter::direct(quota))
             });
 
         pub static RATE_LIMITER_PER_MINUTE: Lazy<Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock>>> =
             Lazy::new(|| {
-                let quota = Quota::per_minute(NonZeroU32::new(60).unwrap());
+                let per_minute = UTILS_CONFIG.get().map_or(60, |config| config.apis.jikan_requests_per_minute);
+                let quota = Quota::per_minute(NonZeroU32::new(per_minute).unwrap_or(NonZeroU32::MIN));
                 Arc::new(RateLimiter::direct(quota))
             });
+
+        #[derive(Deserialize)]
+        pub struct JikanData<T> {
+            pub data: T,
+        }
+
+        #[derive(Deserialize)]
+        pub struct JikanPage<T> {
+            pub pagination: JikanPagination,
+            pub data: Vec<T>,
+        }
+
+        #[derive(Deserialize)]
+        pub struct JikanPagination {
+            pub last_visible_page: i32,
+            pub has_next_page: bool,
+            pub items: JikanItems,
+        }
+
+        #[derive(Deserialize)]
+        pub struct JikanItems {
+            pub count: i32,
+            pub total: usize,
+        }
+
+        async fn fetch<T: DeserializeOwned>(request_url: &str) -> Result<Option<T>, AppError> {
+            let (max_retries, base_delay_ms) = UTILS_CONFIG
+                .get()
+                .map_or((5, 1000), |config| (config.apis.max_retries, config.apis.retry_base_delay_ms));
+            let mut attempt = 0;
+            loop {
+                if !replaying() {
+                    RATE_LIMITER_PER_SECOND.until_ready().await;
+                    RATE_LIMITER_PER_MINUTE.until_ready().await;
+                }
+                let (error, retry_after) = match get(request_url).await {
+                    Ok(resp) if resp.status() == StatusCode::NOT_FOUND => return Ok(None),
+                    Ok(resp) if resp.status().is_success() => {
+                        return resp.json::<T>().map(Some).map_err(|e| {
+                            AppError::ProviderError(format!("Jikan response {request_url}: {e}"))
+                        });
+                    }
+                    Ok(resp)
+                        if resp.status() == StatusCode::TOO_MANY_REQUESTS
+                            || resp.status().is_server_error() =>
+                    {
+                        let retry_after = resp
+                            .retry_after
+                            .as_deref()
+                            .and_then(|value| value.trim().parse::<u64>().ok())
+                            .map(Duration::from_secs);
+                        (format!("status {}", resp.status()), retry_after)
+                    }
+                    Ok(resp) => {
+                        return Err(AppError::ProviderError(format!(
+                            "Jikan request {request_url}: status {}",
+                            resp.status()
+                        )));
+                    }
+                    Err(AppError::ProviderError(e)) => (e, None),
+                    Err(e) => return Err(e),
+                };
+
+                if attempt >= max_retries {
+                    return Err(AppError::ProviderError(format!(
+                        "Jikan request {request_url} failed after {} attempts: {error}",
+                        attempt + 1
+                    )));
+                }
+                let delay = match retry_after {
+                    Some(delay) => delay,
+                    None => backoff(base_delay_ms, attempt).await,
+                };
+                warn!("Jikan request {request_url} failed ({error}), retrying in {delay:?}");
+                sleep(delay).await;
+                attempt += 1;
+            }
+        }
+
+        async fn backoff(base_delay_ms: u64, attempt: u32) -> Duration {
+            let delay_ms = base_delay_ms.saturating_mul(2_u64.saturating_pow(attempt));
+            let jitter_ms = RNG.lock().await.u64(0..=delay_ms / 2);
+            Duration::from_millis(delay_ms + jitter_ms)
+        }
+
+        fn page_url(endpoint: &str, page: i32) -> String {
+            let separator = if endpoint.contains('?') { '&' } else { '?' };
+            format!("{}/{endpoint}{separator}page={page}", base_url())
+        }
+
+        pub async fn search<T: DeserializeOwned>(
+            endpoint: &str,
+            query: &str,
+            page: i32,
+        ) -> Result<SearchHits<T>, AppError> {
+            let request_url = format!(
+                "{}/{endpoint}?q={}&page={page}&limit={SEARCH_PAGE_SIZE}",
+                base_url(),
+                encode(query)
+            );
+            Ok(match fetch::<JikanPage<T>>(&request_url).await? {
+                Some(data) => SearchHits {
+                    items: data.data,
+                    has_next_page: data.pagination.has_next_page,
+                    total: Some(data.pagination.items.total),
+                },
+                None => SearchHits {
+                    items: Vec::default(),
+                    has_next_page: false,
+                    total: Some(0),
+                },
+            })
+        }
+
+        pub async fn by_id<T: DeserializeOwned>(endpoint: &str, id: i32) -> Result<Option<T>, AppError> {
+            let request_url = format!("{}/{endpoint}/{id}", base_url());
+            Ok(fetch::<JikanData<T>>(&request_url).await?.map(|data| data.data))
+        }
+
+        pub async fn catalog_page<T: DeserializeOwned>(
+            endpoint: &str,
+            page: i32,
+        ) -> Result<CatalogPage<T>, AppError> {
+            Ok(match fetch::<JikanPage<T>>(&page_url(endpoint, page)).await? {
+                Some(data) => CatalogPage {
+                    items: data.data,
+                    has_next_page: data.pagination.has_next_page,
+                },
+                None => CatalogPage {
+                    items: Vec::default(),
+                    has_next_page: false,
+                },
+            })
+        }
+
+        pub async fn all_pages<T: DeserializeOwned>(endpoint: &str) -> Result<Vec<T>, AppError> {
+            let initial_url = format!("{}/{endpoint}", base_url());
+            let Some(initial_data) = fetch::<JikanPage<T>>(&initial_url).await? else {
+                return Ok(Vec::default());
+            };
+            let total_pages = initial_data.pagination.last_visible_page;
+
+            let semaphore = Arc::new(Semaphore::new(3));
+
+            let fetch_page = move |page: i32| {
+                let semaphore_clone = semaphore.clone();
+                async move {
+                    let _permit = semaphore_clone.acquire().await.unwrap();
+                    let result = fetch::<JikanPage<T>>(&page_url(endpoint, page)).await;
+                    (page, result)
+                }
+            };
+
+            let page_futures: Vec<_> = (1..=total_pages).map(fetch_page).collect();
+            let mut items = Vec::new();
+            let mut failed_pages = Vec::new();
+            for (page, result) in join_all(page_futures).await {
+                match result {
+                    Ok(data) => items.extend(data.into_iter().flat_map(|page| page.data)),
+                    Err(_) => failed_pages.push(page),
+                }
+            }
+            // A missing page would silently drop its updates, so the whole sync fails and retries
+            if !failed_pages.is_empty() {
+                return Err(AppError::ProviderError(format!(
+                    "Jikan {endpoint}: pages {failed_pages:?} failed permanently"
+                )));
+            }
+
+            Ok(items)
+        }
+
+        pub async fn pages_until<T: DeserializeOwned>(
+            endpoint: &str,
+            stop: impl Fn(&T) -> bool,
+        ) -> Result<Vec<T>, AppError> {
+            let mut items = Vec::new();
+            let mut current_page = 1;
+            while let Some(page) = fetch::<JikanPage<T>>(&page_url(endpoint, current_page)).await? {
+                let page_size = page.data.len();
+                let fresh: Vec<T> = page.data.into_iter().take_while(|item| !stop(item)).collect();
+                let exhausted = fresh.len() < page_size || !page.pagination.has_next_page;
+                items.extend(fresh);
+                if exhausted {
+                    break;
+                }
+                current_page += 1;
+            }
+
+            Ok(items)
+        }
     }
 }
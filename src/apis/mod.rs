@@ -1,3 +1,16 @@
+use cfg_if::cfg_if;
+
 pub mod anilist;
+pub mod identity;
 pub mod jikan;
-pub mod tracker;
+pub mod model;
+pub mod status;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod http;
+        pub mod indexer;
+        pub mod provider;
+        pub mod tracker;
+    }
+}
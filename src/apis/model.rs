@@ -0,0 +1,721 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    #[default]
+    Jikan,
+    Anilist,
+}
+
+impl Provider {
+    pub const ALL: [Self; 2] = [Self::Jikan, Self::Anilist];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Jikan => "jikan",
+            Self::Anilist => "anilist",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|provider| provider.as_str() == name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaKind {
+    Anime,
+    Manga,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Entity {
+    Anime,
+    Manga,
+    Character,
+}
+
+impl Entity {
+    pub const ALL: [Self; 3] = [Self::Anime, Self::Manga, Self::Character];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Anime => "anime",
+            Self::Manga => "manga",
+            Self::Character => "character",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|entity| entity.as_str() == name)
+    }
+
+    #[must_use]
+    pub const fn media_kind(self) -> Option<MediaKind> {
+        match self {
+            Self::Anime => Some(MediaKind::Anime),
+            Self::Manga => Some(MediaKind::Manga),
+            Self::Character => None,
+        }
+    }
+}
+
+impl From<MediaKind> for Entity {
+    fn from(kind: MediaKind) -> Self {
+        match kind {
+            MediaKind::Anime => Self::Anime,
+            MediaKind::Manga => Self::Manga,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ProviderId {
+    pub provider: Provider,
+    pub id: i32,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CatalogIdentity {
+    pub canonical_id: i64,
+    pub entity: Entity,
+    pub ids: Vec<ProviderId>,
+}
+
+#[must_use]
+pub fn index_name(provider: Provider, entity: Entity) -> String {
+    format!("{}_{}", provider.as_str(), entity.as_str())
+}
+
+#[must_use]
+pub const fn canonical_index_name(entity: Entity) -> &'static str {
+    entity.as_str()
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Media {
+    pub id: i32,
+    pub provider: Provider,
+    pub kind: MediaKind,
+    pub mal_id: Option<i32>,
+    pub url: String,
+    pub image_url: Option<String>,
+    pub titles: Vec<MediaTitle>,
+    pub synonyms: Vec<String>,
+    #[serde(rename = "type")]
+    pub media_type: Option<String>,
+    pub status: Option<String>,
+    pub rating: Option<String>,
+    pub episodes: Option<i32>,
+    pub chapters: Option<i32>,
+    pub volumes: Option<i32>,
+    pub score: Option<f32>,
+    pub rank: Option<i32>,
+    /// Users with the entry on their list, so higher is more popular
+    pub popularity: Option<i32>,
+    pub favorites: Option<i32>,
+    pub synopsis: Option<String>,
+    pub season: Option<String>,
+    pub year: Option<i32>,
+    pub genres: Vec<String>,
+    pub tags: Vec<String>,
+    pub creators: Vec<String>,
+}
+
+impl Media {
+    #[must_use]
+    pub fn title(&self) -> &str {
+        self.titles.first().map_or("", |title| title.title.as_str())
+    }
+}
+
+const IMAGE_SOURCES: [Provider; 2] = [Provider::Anilist, Provider::Jikan];
+const STATS_SOURCES: [Provider; 2] = [Provider::Jikan, Provider::Anilist];
+const DETAIL_SOURCES: [Provider; 2] = [Provider::Jikan, Provider::Anilist];
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MediaSource {
+    pub provider: Provider,
+    pub id: i32,
+    pub url: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CanonicalMedia {
+    pub id: i64,
+    pub kind: MediaKind,
+    pub sources: Vec<MediaSource>,
+    pub image_url: Option<String>,
+    pub titles: Vec<MediaTitle>,
+    pub synonyms: Vec<String>,
+    #[serde(rename = "type")]
+    pub media_type: Option<String>,
+    pub status: Option<String>,
+    pub rating: Option<String>,
+    pub episodes: Option<i32>,
+    pub chapters: Option<i32>,
+    pub volumes: Option<i32>,
+    pub score: Option<f32>,
+    pub rank: Option<i32>,
+    pub popularity: Option<i32>,
+    pub favorites: Option<i32>,
+    pub synopsis: Option<String>,
+    pub season: Option<String>,
+    pub year: Option<i32>,
+    pub genres: Vec<String>,
+    pub tags: Vec<String>,
+    pub creators: Vec<String>,
+    pub provenance: BTreeMap<String, Vec<Provider>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+}
+
+impl CanonicalMedia {
+    #[must_use]
+    pub fn merge(id: i64, records: &[Media]) -> Option<Self> {
+        let kind = records.first()?.kind;
+        let mut provenance = BTreeMap::new();
+
+        Some(Self {
+            id,
+            kind,
+            sources: records
+                .iter()
+                .map(|record| MediaSource {
+                    provider: record.provider,
+                    id: record.id,
+                    url: record.url.clone(),
+                })
+                .collect(),
+            titles: merge_titles(records, &mut provenance),
+            synonyms: merge_list(records, &mut provenance, "synonyms", |m| &m.synonyms),
+            image_url: pick_field(records, &mut provenance, "image_url", IMAGE_SOURCES, |m| {
+                m.image_url.clone()
+            }),
+            media_type: pick_field(records, &mut provenance, "type", DETAIL_SOURCES, |m| {
+                m.media_type.clone()
+            }),
+            status: pick_field(records, &mut provenance, "status", DETAIL_SOURCES, |m| {
+                m.status.clone()
+            }),
+            rating: pick_field(records, &mut provenance, "rating", DETAIL_SOURCES, |m| {
+                m.rating.clone()
+            }),
+            episodes: pick_field(records, &mut provenance, "episodes", DETAIL_SOURCES, |m| {
+                m.episodes
+            }),
+            chapters: pick_field(records, &mut provenance, "chapters", DETAIL_SOURCES, |m| {
+                m.chapters
+            }),
+            volumes: pick_field(records, &mut provenance, "volumes", DETAIL_SOURCES, |m| {
+                m.volumes
+            }),
+            score: pick_field(records, &mut provenance, "score", STATS_SOURCES, |m| {
+                m.score
+            }),
+            rank: pick_field(records, &mut provenance, "rank", STATS_SOURCES, |m| m.rank),
+            popularity: pick_field(records, &mut provenance, "popularity", STATS_SOURCES, |m| {
+                m.popularity
+            }),
+            favorites: pick_field(records, &mut provenance, "favorites", STATS_SOURCES, |m| {
+                m.favorites
+            }),
+            synopsis: pick_field(records, &mut provenance, "synopsis", DETAIL_SOURCES, |m| {
+                m.synopsis.clone()
+            }),
+            season: pick_field(records, &mut provenance, "season", DETAIL_SOURCES, |m| {
+                m.season.clone()
+            }),
+            year: pick_field(records, &mut provenance, "year", DETAIL_SOURCES, |m| m.year),
+            genres: merge_list(records, &mut provenance, "genres", |m| &m.genres),
+            tags: merge_list(records, &mut provenance, "tags", |m| &m.tags),
+            creators: merge_list(records, &mut provenance, "creators", |m| &m.creators),
+            provenance,
+            highlights: Vec::new(),
+        })
+    }
+
+    #[must_use]
+    pub fn title(&self) -> &str {
+        self.titles.first().map_or("", |title| title.title.as_str())
+    }
+}
+
+fn pick_field<T>(
+    records: &[Media],
+    provenance: &mut BTreeMap<String, Vec<Provider>>,
+    field: &str,
+    order: [Provider; 2],
+    value: impl Fn(&Media) -> Option<T>,
+) -> Option<T> {
+    order.into_iter().find_map(|provider| {
+        let value = records
+            .iter()
+            .filter(|record| record.provider == provider)
+            .find_map(&value)?;
+        provenance.insert(field.to_string(), vec![provider]);
+        Some(value)
+    })
+}
+
+fn merge_list(
+    records: &[Media],
+    provenance: &mut BTreeMap<String, Vec<Provider>>,
+    field: &str,
+    values: impl Fn(&Media) -> &Vec<String>,
+) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::new();
+    let mut sources = Vec::new();
+    for provider in DETAIL_SOURCES {
+        for record in records.iter().filter(|record| record.provider == provider) {
+            for value in values(record) {
+                if !merged.iter().any(|known| known.eq_ignore_ascii_case(value)) {
+                    merged.push(value.clone());
+                    if !sources.contains(&provider) {
+                        sources.push(provider);
+                    }
+                }
+            }
+        }
+    }
+    if !sources.is_empty() {
+        provenance.insert(field.to_string(), sources);
+    }
+    merged
+}
+
+fn merge_titles(
+    records: &[Media],
+    provenance: &mut BTreeMap<String, Vec<Provider>>,
+) -> Vec<MediaTitle> {
+    let mut titles: Vec<MediaTitle> = Vec::new();
+    let mut sources = Vec::new();
+    for provider in DETAIL_SOURCES {
+        for record in records.iter().filter(|record| record.provider == provider) {
+            for title in &record.titles {
+                if !titles.iter().any(|known| known.title == title.title) {
+                    titles.push(title.clone());
+                    if !sources.contains(&provider) {
+                        sources.push(provider);
+                    }
+                }
+            }
+        }
+    }
+    if !sources.is_empty() {
+        provenance.insert("titles".to_string(), sources);
+    }
+    titles
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct MediaTitle {
+    #[serde(rename = "type")]
+    pub title_type: String,
+    pub title: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Character {
+    pub id: i32,
+    pub provider: Provider,
+    pub url: String,
+    pub image_url: Option<String>,
+    pub name: String,
+    pub name_native: Option<String>,
+    pub nicknames: Vec<String>,
+    pub favorites: Option<i32>,
+    pub about: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+}
+
+/// A matched field of a search hit, split into plain and matched fragments so the page can
+/// mark them up without rendering any HTML from the search engine
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub field: String,
+    pub fragments: Vec<HighlightFragment>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct HighlightFragment {
+    pub text: String,
+    pub matched: bool,
+}
+
+impl Highlight {
+    #[must_use]
+    pub fn label(&self) -> &'static str {
+        match self.field.as_str() {
+            "titles" | "synonyms" => "Also known as",
+            "synopsis" => "Synopsis",
+            "name_native" => "Native name",
+            "nicknames" => "Nickname",
+            _ => "About",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ScraperStatus {
+    pub provider: Provider,
+    pub entity: Entity,
+    pub last_success: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<i64>,
+    pub consecutive_failures: u32,
+    pub next_run: Option<i64>,
+}
+
+impl ScraperStatus {
+    #[must_use]
+    pub const fn new(provider: Provider, entity: Entity) -> Self {
+        Self {
+            provider,
+            entity,
+            last_success: None,
+            last_error: None,
+            last_error_at: None,
+            consecutive_failures: 0,
+            next_run: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CacheStats {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: u64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Person {
+    pub id: i32,
+    pub provider: Provider,
+    pub url: String,
+    pub image_url: Option<String>,
+    pub name: String,
+    pub name_native: Option<String>,
+    pub alternate_names: Vec<String>,
+    pub occupations: Vec<String>,
+    pub favorites: Option<i32>,
+    pub about: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchCategory {
+    #[default]
+    All,
+    Anime,
+    Manga,
+    Character,
+    People,
+}
+
+impl SearchCategory {
+    pub const ALL: [Self; 5] = [
+        Self::All,
+        Self::Anime,
+        Self::Manga,
+        Self::Character,
+        Self::People,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Anime => "anime",
+            Self::Manga => "manga",
+            Self::Character => "character",
+            Self::People => "people",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == name)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "category", content = "result", rename_all = "lowercase")]
+pub enum SearchResult {
+    Anime(CanonicalMedia),
+    Manga(CanonicalMedia),
+    Character(Character),
+    People(Person),
+}
+
+impl SearchResult {
+    #[must_use]
+    pub const fn category(&self) -> SearchCategory {
+        match self {
+            Self::Anime(_) => SearchCategory::Anime,
+            Self::Manga(_) => SearchCategory::Manga,
+            Self::Character(_) => SearchCategory::Character,
+            Self::People(_) => SearchCategory::People,
+        }
+    }
+
+    // Canonical ids start at 1, so media the catalog doesn't know yet is keyed by its
+    // provider id instead
+    #[must_use]
+    pub fn key(&self) -> String {
+        let id = match self {
+            Self::Anime(media) | Self::Manga(media) => match media.sources.first() {
+                Some(source) if media.id == 0 => {
+                    format!("{}-{}", source.provider.as_str(), source.id)
+                }
+                _ => media.id.to_string(),
+            },
+            Self::Character(character) => character.id.to_string(),
+            Self::People(person) => person.id.to_string(),
+        };
+        format!("{}:{id}", self.category().as_str())
+    }
+}
+
+pub const MEDIA_FACETS: [&str; 6] = ["genres", "year", "season", "status", "type", "rating"];
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+pub struct SearchFilters {
+    pub genres: Vec<String>,
+    pub exclude_genres: Vec<String>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub season: Option<String>,
+    pub status: Option<String>,
+    pub media_type: Option<String>,
+    pub min_score: Option<f32>,
+    pub rating: Option<String>,
+}
+
+impl SearchFilters {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.genres.extend(other.genres);
+        self.exclude_genres.extend(other.exclude_genres);
+        self.year_from = other.year_from.or(self.year_from);
+        self.year_to = other.year_to.or(self.year_to);
+        self.season = other.season.or(self.season);
+        self.status = other.status.or(self.status);
+        self.media_type = other.media_type.or(self.media_type);
+        self.min_score = other.min_score.or(self.min_score);
+        self.rating = other.rating.or(self.rating);
+        self
+    }
+
+    #[must_use]
+    pub fn filter_expression(&self) -> Option<String> {
+        let quote =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut clauses = Vec::new();
+        clauses.extend(
+            self.genres
+                .iter()
+                .map(|genre| format!("genres = {}", quote(genre))),
+        );
+        clauses.extend(
+            self.exclude_genres
+                .iter()
+                .map(|genre| format!("NOT genres = {}", quote(genre))),
+        );
+        clauses.extend(self.year_from.map(|year| format!("year >= {year}")));
+        clauses.extend(self.year_to.map(|year| format!("year <= {year}")));
+        clauses.extend(self.min_score.map(|score| format!("score >= {score}")));
+        for (field, value) in [
+            ("season", &self.season),
+            ("status", &self.status),
+            ("type", &self.media_type),
+            ("rating", &self.rating),
+        ] {
+            clauses.extend(
+                value
+                    .as_deref()
+                    .map(|value| format!("{field} = {}", quote(value))),
+            );
+        }
+
+        (!clauses.is_empty()).then(|| clauses.join(" AND "))
+    }
+
+    #[must_use]
+    pub fn matches(&self, media: &CanonicalMedia) -> bool {
+        let same = |filter: &Option<String>, value: &Option<String>| {
+            filter.as_ref().is_none_or(|filter| {
+                value
+                    .as_ref()
+                    .is_some_and(|value| value.eq_ignore_ascii_case(filter))
+            })
+        };
+        let has_genre = |genre: &String| media.genres.iter().any(|g| g.eq_ignore_ascii_case(genre));
+
+        self.genres.iter().all(has_genre)
+            && !self.exclude_genres.iter().any(has_genre)
+            && self
+                .year_from
+                .is_none_or(|from| media.year.is_some_and(|year| year >= from))
+            && self
+                .year_to
+                .is_none_or(|to| media.year.is_some_and(|year| year <= to))
+            && self
+                .min_score
+                .is_none_or(|min| media.score.is_some_and(|score| score >= min))
+            && same(&self.season, &media.season)
+            && same(&self.status, &media.status)
+            && same(&self.media_type, &media.media_type)
+            && same(&self.rating, &media.rating)
+    }
+
+    #[must_use]
+    pub fn from_query(get: impl Fn(&str) -> Option<String>) -> Self {
+        let list = |key: &str| {
+            get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|value| !value.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let text = |key: &str| get(key).filter(|value| !value.is_empty());
+
+        Self {
+            genres: list("genres"),
+            exclude_genres: list("exclude_genres"),
+            year_from: text("year_from").and_then(|year| year.parse().ok()),
+            year_to: text("year_to").and_then(|year| year.parse().ok()),
+            season: text("season"),
+            status: text("status"),
+            media_type: text("type"),
+            // nan and inf parse as floats, but no score compares against them
+            min_score: text("min_score")
+                .and_then(|score| score.parse().ok())
+                .filter(|score: &f32| score.is_finite()),
+            rating: text("rating"),
+        }
+    }
+
+    #[must_use]
+    pub fn to_query(&self) -> String {
+        let list = |values: &[String]| (!values.is_empty()).then(|| values.join(","));
+        [
+            ("genres", list(&self.genres)),
+            ("exclude_genres", list(&self.exclude_genres)),
+            ("year_from", self.year_from.map(|year| year.to_string())),
+            ("year_to", self.year_to.map(|year| year.to_string())),
+            ("season", self.season.clone()),
+            ("status", self.status.clone()),
+            ("type", self.media_type.clone()),
+            ("min_score", self.min_score.map(|score| score.to_string())),
+            ("rating", self.rating.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| {
+            value.map(|value| format!("&{key}={}", urlencoding::encode(&value)))
+        })
+        .collect()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    pub facets: BTreeMap<String, BTreeMap<String, usize>>,
+    pub page: i32,
+    pub total_hits: Option<usize>,
+    pub total_pages: Option<usize>,
+    pub has_next_page: bool,
+    pub groups: Vec<SearchGroup>,
+    pub corrections: Vec<String>,
+    pub event_id: Option<i64>,
+}
+
+impl SearchResponse {
+    #[must_use]
+    pub fn result_count(&self) -> usize {
+        self.results.len()
+            + self
+                .groups
+                .iter()
+                .map(|group| group.results.len())
+                .sum::<usize>()
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SearchGroup {
+    pub category: SearchCategory,
+    pub total: Option<usize>,
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct TrendingSearch {
+    pub query: String,
+    pub category: SearchCategory,
+    pub searches: i64,
+}
+
+impl TrendingSearch {
+    #[must_use]
+    pub fn href(&self) -> String {
+        format!(
+            "/search/{}?q={}",
+            self.category.as_str(),
+            urlencoding::encode(&self.query)
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct ZeroResultSearch {
+    pub query: String,
+    pub category: SearchCategory,
+    pub filters: String,
+    pub searches: i64,
+    pub last_searched: i64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Suggestion {
+    pub category: SearchCategory,
+    pub title: String,
+    pub image_url: Option<String>,
+}
+
+impl Suggestion {
+    #[must_use]
+    pub fn href(&self) -> String {
+        format!(
+            "/search/{}?q={}",
+            self.category.as_str(),
+            urlencoding::encode(&self.title)
+        )
+    }
+}
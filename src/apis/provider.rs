@@ -0,0 +1,124 @@
+use crate::{
+    apis::{
+        anilist::provider::AnilistProvider,
+        jikan::provider::JikanProvider,
+        model::{Character, Media, MediaKind, Person, Provider, ProviderId},
+    },
+    errors::error_template::AppError,
+};
+use async_trait::async_trait;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyncCursor {
+    pub last_synced_at: Option<i64>,
+    pub known_id: Option<i32>,
+}
+
+pub struct CatalogPage<T> {
+    pub items: Vec<T>,
+    pub has_next_page: bool,
+}
+
+pub struct SearchHits<T> {
+    pub items: Vec<T>,
+    pub has_next_page: bool,
+    pub total: Option<usize>,
+}
+
+impl<T> SearchHits<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> SearchHits<U> {
+        SearchHits {
+            items: self.items.into_iter().map(f).collect(),
+            has_next_page: self.has_next_page,
+            total: self.total,
+        }
+    }
+}
+
+#[async_trait]
+pub trait MetadataProvider: Send + Sync {
+    fn provider(&self) -> Provider;
+
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Media>, AppError>;
+
+    async fn media_by_id(&self, kind: MediaKind, id: i32) -> Result<Option<Media>, AppError>;
+
+    async fn media_page(&self, kind: MediaKind, page: i32) -> Result<CatalogPage<Media>, AppError>;
+
+    async fn updated_media(
+        &self,
+        kind: MediaKind,
+        cursor: SyncCursor,
+    ) -> Result<Vec<Media>, AppError>;
+
+    async fn search_characters(
+        &self,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError>;
+
+    async fn character_by_id(&self, id: i32) -> Result<Option<Character>, AppError>;
+
+    async fn character_page(&self, page: i32) -> Result<CatalogPage<Character>, AppError>;
+
+    async fn updated_characters(&self, cursor: SyncCursor) -> Result<Vec<Character>, AppError>;
+
+    async fn search_people(&self, query: &str, page: i32) -> Result<SearchHits<Person>, AppError>;
+}
+
+impl Provider {
+    #[must_use]
+    pub fn metadata(self) -> &'static dyn MetadataProvider {
+        match self {
+            Self::Jikan => &JikanProvider,
+            Self::Anilist => &AnilistProvider,
+        }
+    }
+}
+
+pub trait CatalogDocument: Serialize + Send + Sync {
+    fn id(&self) -> i32;
+
+    fn provider_ids(&self) -> Vec<ProviderId>;
+}
+
+impl CatalogDocument for Media {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn provider_ids(&self) -> Vec<ProviderId> {
+        let mut ids = vec![ProviderId {
+            provider: self.provider,
+            id: self.id,
+        }];
+        if self.provider != Provider::Jikan
+            && let Some(mal_id) = self.mal_id
+        {
+            ids.push(ProviderId {
+                provider: Provider::Jikan,
+                id: mal_id,
+            });
+        }
+        ids
+    }
+}
+
+impl CatalogDocument for Character {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn provider_ids(&self) -> Vec<ProviderId> {
+        vec![ProviderId {
+            provider: self.provider,
+            id: self.id,
+        }]
+    }
+}
@@ -0,0 +1,77 @@
+use crate::apis::model::{
+    CacheStats, Entity, Provider, ScraperStatus, ZeroResultSearch, index_name,
+};
+use cfg_if::cfg_if;
+use leptos::prelude::*;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::{
+            apis::{indexer::reindex, tracker::{scraper_statuses, trigger_sync}},
+            auth::server::require_admin,
+            config::consts::{ZERO_RESULT_REPORT_DAYS, ZERO_RESULT_REPORT_LIMIT},
+            db::backend::PostgreSQLBackend,
+            utils::cache::{cache_stats, invalidate_caches},
+        };
+        use axum_login::AuthSession;
+        use tokio::task::spawn;
+        use tracing::{error, info};
+    }
+}
+
+#[server(ScrapingStatus, "/api", "Url", "scraping_status")]
+pub async fn scraping_status() -> Result<Vec<ScraperStatus>, ServerFnError> {
+    require_admin()?;
+
+    Ok(scraper_statuses().await)
+}
+
+#[server(CacheStatistics, "/api", "Url", "cache_statistics")]
+pub async fn cache_statistics() -> Result<Vec<CacheStats>, ServerFnError> {
+    require_admin()?;
+
+    Ok(cache_stats())
+}
+
+#[server(ZeroResultSearches, "/api", "Url", "zero_result_searches")]
+pub async fn zero_result_searches() -> Result<Vec<ZeroResultSearch>, ServerFnError> {
+    require_admin()?;
+    let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+
+    Ok(session
+        .backend
+        .zero_result_searches(ZERO_RESULT_REPORT_DAYS, ZERO_RESULT_REPORT_LIMIT)
+        .await?)
+}
+
+#[server(StartSync, "/api", "Url", "start_sync")]
+pub async fn start_sync(provider: Provider, entity: Entity) -> Result<(), ServerFnError> {
+    let admin = require_admin()?;
+    info!(
+        "{} requested a sync of {}",
+        admin.username,
+        index_name(provider, entity)
+    );
+    trigger_sync(provider, entity);
+
+    Ok(())
+}
+
+#[server(RebuildIndex, "/api", "Url", "rebuild_index")]
+pub async fn rebuild_search_index(provider: Provider, entity: Entity) -> Result<(), ServerFnError> {
+    let admin = require_admin()?;
+    let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+    info!(
+        "{} requested a rebuild of {}",
+        admin.username,
+        index_name(provider, entity)
+    );
+    spawn(async move {
+        match reindex(&session.backend, provider, entity).await {
+            Ok(_) => invalidate_caches(),
+            Err(e) => error!("Rebuilding {} failed: {e}", index_name(provider, entity)),
+        }
+    });
+
+    Ok(())
+}
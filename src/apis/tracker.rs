@@ -1,10 +1,462 @@
-use leptos::prelude::*;
+use crate::{
+    apis::{
+        indexer::{index_records, reindex},
+        model::{Entity, Provider, ScraperStatus, index_name},
+        provider::{CatalogDocument, SyncCursor},
+    },
+    config::{
+        consts::{SCRAPE_MAX_CONSECUTIVE_FAILURES, SCRAPE_RETRY_BASE_SECONDS},
+        settings::APISConfig,
+        types::{MEILISEARCH_CLIENT, RNG, SCRAPER_STATUS, SCRAPER_TRIGGERS},
+    },
+    db::{backend::PostgreSQLBackend, scrape::ScrapeRun},
+    errors::error_template::AppError,
+    utils::{cache::invalidate_caches, hash::fnv1a},
+};
+use std::collections::HashMap;
+use time::{Duration as TimeDuration, OffsetDateTime};
+use tokio::{
+    task::spawn,
+    time::{Duration, timeout},
+};
+use tracing::{error, info, warn};
 
-#[server]
-pub async fn start_api_scraping_tasks(fetch_interval: u64) -> Result<(), ServerFnError> {
-    use crate::apis::{anilist::tracker::*, jikan::tracker::*};
-    jikan_scraping(fetch_interval).await.unwrap();
-    anilist_scraping(fetch_interval).await.unwrap();
+pub async fn start_api_scraping_tasks(
+    backend: PostgreSQLBackend,
+    config: &APISConfig,
+) -> Result<(), AppError> {
+    let interval = Duration::from_hours(config.fetch_interval_hours);
+    let full_sync_interval = TimeDuration::hours(config.full_sync_interval_hours as i64);
+    for provider in Provider::ALL {
+        for entity in Entity::ALL {
+            SCRAPER_STATUS
+                .lock()
+                .await
+                .insert((provider, entity), ScraperStatus::new(provider, entity));
+            spawn(supervise_scraping(
+                backend.clone(),
+                provider,
+                entity,
+                interval,
+                full_sync_interval,
+            ));
+        }
+    }
 
     Ok(())
 }
+
+pub fn trigger_sync(provider: Provider, entity: Entity) {
+    if let Some(trigger) = SCRAPER_TRIGGERS.get(&(provider, entity)) {
+        trigger.notify_one();
+    }
+}
+
+pub async fn scraper_statuses() -> Vec<ScraperStatus> {
+    let mut statuses: Vec<ScraperStatus> = SCRAPER_STATUS.lock().await.values().cloned().collect();
+    statuses.sort_by_key(|status| (status.provider.as_str(), status.entity.as_str()));
+    statuses
+}
+
+async fn supervise_scraping(
+    backend: PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    interval: Duration,
+    full_sync_interval: TimeDuration,
+) {
+    let name = index_name(provider, entity);
+    let mut delay = initial_delay(&backend, provider, entity, interval)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(job = %name, "Could not determine sync staleness: {e}");
+            Duration::ZERO
+        });
+    loop {
+        update_status(provider, entity, |status| {
+            status.next_run = Some(unix_after(delay));
+        })
+        .await;
+        if timeout(delay, SCRAPER_TRIGGERS[&(provider, entity)].notified())
+            .await
+            .is_ok()
+        {
+            info!(job = %name, "Sync triggered on demand");
+        }
+
+        let job_backend = backend.clone();
+        let result = spawn(async move {
+            sync_entity(&job_backend, provider, entity, full_sync_interval).await
+        })
+        .await
+        .unwrap_or_else(|e| {
+            Err(AppError::InternalError(format!(
+                "Scraping task panicked: {e}"
+            )))
+        });
+
+        match result {
+            Ok(_) => {
+                update_status(provider, entity, |status| {
+                    status.last_success = Some(OffsetDateTime::now_utc().unix_timestamp());
+                    status.consecutive_failures = 0;
+                })
+                .await;
+                delay = interval;
+            }
+            Err(e) => {
+                let mut failures = 0;
+                update_status(provider, entity, |status| {
+                    status.last_error = Some(e.to_string());
+                    status.last_error_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+                    status.consecutive_failures += 1;
+                    failures = status.consecutive_failures;
+                })
+                .await;
+                delay = retry_delay(failures, interval).await;
+                error!(
+                    job = %name,
+                    failures,
+                    retry_in_seconds = delay.as_secs(),
+                    "Scraping failed: {e}"
+                );
+            }
+        }
+    }
+}
+
+async fn initial_delay(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    interval: Duration,
+) -> Result<Duration, AppError> {
+    let index_empty = match MEILISEARCH_CLIENT.get() {
+        Some(client) => client
+            .index(index_name(provider, entity))
+            .get_stats()
+            .await
+            .map_or(true, |stats| stats.number_of_documents == 0),
+        None => false,
+    };
+    if index_empty {
+        if backend.record_count(provider, entity).await? == 0 {
+            return Ok(Duration::ZERO);
+        }
+        if let Err(e) = reindex(backend, provider, entity).await {
+            warn!("Reindex of {} failed: {e}", index_name(provider, entity));
+        }
+    }
+    if backend.running_scrape(provider, entity).await?.is_some() {
+        return Ok(Duration::ZERO);
+    }
+
+    let state = backend.sync_state(provider, entity).await?;
+    Ok(state.last_incremental_sync.map_or(Duration::ZERO, |last| {
+        let elapsed = Duration::try_from(OffsetDateTime::now_utc() - last).unwrap_or_default();
+        interval.saturating_sub(elapsed)
+    }))
+}
+
+async fn update_status(
+    provider: Provider,
+    entity: Entity,
+    update: impl FnOnce(&mut ScraperStatus),
+) {
+    let mut statuses = SCRAPER_STATUS.lock().await;
+    update(
+        statuses
+            .entry((provider, entity))
+            .or_insert_with(|| ScraperStatus::new(provider, entity)),
+    );
+}
+
+async fn retry_delay(failures: u32, interval: Duration) -> Duration {
+    let backoff = Duration::from_secs(SCRAPE_RETRY_BASE_SECONDS)
+        .saturating_mul(2_u32.saturating_pow(failures.saturating_sub(1)))
+        .min(interval);
+    let jitter = RNG.lock().await.u64(0..=backoff.as_secs() / 4);
+    backoff + Duration::from_secs(jitter)
+}
+
+fn unix_after(delay: Duration) -> i64 {
+    (OffsetDateTime::now_utc() + delay).unix_timestamp()
+}
+
+pub async fn sync_entity(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    full_sync_interval: TimeDuration,
+) -> Result<usize, AppError> {
+    let run = match backend.running_scrape(provider, entity).await? {
+        Some(run) if run.full_sync => Some(run),
+        Some(run) => {
+            backend
+                .finish_scrape(run.id, Some("Interrupted before completion".to_string()))
+                .await?;
+            None
+        }
+        None => None,
+    };
+    if let Some(run) = run {
+        info!(
+            "Resuming full sync of {} after page {}",
+            index_name(provider, entity),
+            run.last_page,
+        );
+        return full_crawl(backend, provider, entity, run).await;
+    }
+
+    let state = backend.sync_state(provider, entity).await?;
+    let full_sync = state
+        .last_full_sync
+        .is_none_or(|last| OffsetDateTime::now_utc() - last >= full_sync_interval);
+    let run = backend.start_scrape(provider, entity, full_sync).await?;
+    if full_sync {
+        return full_crawl(backend, provider, entity, run).await;
+    }
+
+    let cursor = SyncCursor {
+        last_synced_at: state
+            .last_incremental_sync
+            .map(OffsetDateTime::unix_timestamp),
+        known_id: state.known_id,
+    };
+    let result = incremental_sync(backend, provider, entity, cursor).await;
+    backend
+        .finish_scrape(run.id, result.as_ref().err().map(ToString::to_string))
+        .await?;
+    let pushed = result?;
+    backend.record_sync(provider, entity, false).await?;
+    invalidate_caches();
+    info!(
+        "Synced {} (incremental): {pushed} documents pushed",
+        index_name(provider, entity),
+    );
+
+    Ok(pushed)
+}
+
+async fn incremental_sync(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    cursor: SyncCursor,
+) -> Result<usize, AppError> {
+    let metadata = provider.metadata();
+    match entity.media_kind() {
+        Some(kind) => {
+            let documents = metadata.updated_media(kind, cursor).await?;
+            let changes = changed_only(backend, provider, entity, documents, false).await?;
+            backend.store_media(provider, kind, &changes).await?;
+            record_changes(backend, provider, entity, &changes, true).await
+        }
+        None => {
+            let documents = metadata.updated_characters(cursor).await?;
+            let changes = changed_only(backend, provider, entity, documents, false).await?;
+            backend.store_characters(provider, &changes).await?;
+            record_changes(backend, provider, entity, &changes, true).await
+        }
+    }
+}
+
+async fn full_crawl(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    run: ScrapeRun,
+) -> Result<usize, AppError> {
+    let (mut pushed, mut failed_pages) =
+        retry_pages(backend, provider, entity, run.id, run.failed_pages).await?;
+
+    let mut page = run.last_page + 1;
+    let mut consecutive_failures = 0;
+    loop {
+        match sync_page(backend, provider, entity, page).await {
+            Ok((count, has_next_page)) => {
+                pushed += count;
+                consecutive_failures = 0;
+                backend.checkpoint_scrape(run.id, page).await?;
+                if !has_next_page {
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "Page {page} of {} failed: {e}",
+                    index_name(provider, entity),
+                );
+                backend.fail_scrape_page(run.id, page).await?;
+                failed_pages.push(page);
+                consecutive_failures += 1;
+                if consecutive_failures >= SCRAPE_MAX_CONSECUTIVE_FAILURES {
+                    return Err(AppError::ProviderError(format!(
+                        "Full sync of {} paused at page {page} after {consecutive_failures} consecutive failures",
+                        index_name(provider, entity),
+                    )));
+                }
+            }
+        }
+        page += 1;
+    }
+
+    // Pages that failed get one more try. Any still failing leave the run running with them
+    // recorded, so the next attempt resumes it and retries them before the sync counts
+    let (recovered, failed_pages) =
+        retry_pages(backend, provider, entity, run.id, failed_pages).await?;
+    pushed += recovered;
+    if !failed_pages.is_empty() {
+        return Err(AppError::ProviderError(format!(
+            "Full sync of {} left pages {failed_pages:?} unsynced, resuming on the next run",
+            index_name(provider, entity),
+        )));
+    }
+
+    // The records are stored either way, so an unreachable Meilisearch doesn't fail the sync
+    if search_indexed()
+        && let Err(e) = reindex(backend, provider, entity).await
+    {
+        warn!(
+            "Reindex of {} failed, keeping the live index: {e}",
+            index_name(provider, entity),
+        );
+    }
+    backend.finish_scrape(run.id, None).await?;
+    backend.record_sync(provider, entity, true).await?;
+    invalidate_caches();
+    info!(
+        "Synced {} (full): {pushed} documents pushed",
+        index_name(provider, entity),
+    );
+
+    Ok(pushed)
+}
+
+async fn retry_pages(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    run_id: i64,
+    pages: Vec<i32>,
+) -> Result<(usize, Vec<i32>), AppError> {
+    let mut pushed = 0;
+    let mut still_failing = Vec::new();
+    for page in pages {
+        match sync_page(backend, provider, entity, page).await {
+            Ok((count, _)) => {
+                pushed += count;
+                backend.recover_scrape_page(run_id, page).await?;
+            }
+            Err(e) => {
+                warn!(
+                    "Page {page} of {} failed again: {e}",
+                    index_name(provider, entity),
+                );
+                still_failing.push(page);
+            }
+        }
+    }
+
+    Ok((pushed, still_failing))
+}
+
+async fn sync_page(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    page: i32,
+) -> Result<(usize, bool), AppError> {
+    let metadata = provider.metadata();
+    match entity.media_kind() {
+        Some(kind) => {
+            let page = metadata.media_page(kind, page).await?;
+            let changes = changed_only(backend, provider, entity, page.items, true).await?;
+            backend.store_media(provider, kind, &changes).await?;
+            let pushed = record_changes(backend, provider, entity, &changes, false).await?;
+            Ok((pushed, page.has_next_page))
+        }
+        None => {
+            let page = metadata.character_page(page).await?;
+            let changes = changed_only(backend, provider, entity, page.items, true).await?;
+            backend.store_characters(provider, &changes).await?;
+            let pushed = record_changes(backend, provider, entity, &changes, false).await?;
+            Ok((pushed, page.has_next_page))
+        }
+    }
+}
+
+async fn changed_only<T: CatalogDocument>(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    documents: Vec<T>,
+    full_sync: bool,
+) -> Result<Vec<T>, AppError> {
+    let mut documents: HashMap<i32, T> = documents
+        .into_iter()
+        .map(|document| (document.id(), document))
+        .collect();
+    if !full_sync {
+        let (ids, fingerprints): (Vec<i32>, Vec<i64>) = documents
+            .iter()
+            .map(|(id, document)| (*id, fingerprint(document)))
+            .unzip();
+        let changed = backend
+            .changed_documents(provider, entity, &ids, &fingerprints)
+            .await?;
+        documents.retain(|id, _| changed.contains(id));
+    }
+
+    Ok(documents.into_values().collect())
+}
+
+async fn record_changes<T: CatalogDocument>(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    entity: Entity,
+    changes: &[T],
+    live: bool,
+) -> Result<usize, AppError> {
+    if changes.is_empty() {
+        return Ok(0);
+    }
+    let ids: Vec<i32> = changes.iter().map(CatalogDocument::id).collect();
+    let fingerprints: Vec<i64> = changes.iter().map(fingerprint).collect();
+    let provider_ids: Vec<_> = changes.iter().map(CatalogDocument::provider_ids).collect();
+    backend
+        .store_fingerprints(provider, entity, &ids, &fingerprints)
+        .await?;
+    for conflict in backend.link_ids(entity, &provider_ids).await? {
+        warn!(
+            "{} {} {} is linked to canonical id {}, not {} like the rest of its document",
+            conflict.id.provider.as_str(),
+            entity.as_str(),
+            conflict.id.id,
+            conflict.linked_to,
+            conflict.expected,
+        );
+    }
+    if live
+        && search_indexed()
+        && let Err(e) = index_records(backend, provider, entity, &ids).await
+    {
+        warn!(
+            "Indexing {} changes to {} failed, the next reindex picks them up: {e}",
+            ids.len(),
+            index_name(provider, entity),
+        );
+    }
+
+    Ok(changes.len())
+}
+
+// Only set up when Meilisearch is the configured backend, so Postgres-only deployments skip
+// indexing altogether
+fn search_indexed() -> bool {
+    MEILISEARCH_CLIENT.get().is_some()
+}
+
+fn fingerprint<T: CatalogDocument>(document: &T) -> i64 {
+    fnv1a(serde_json::to_vec(document).unwrap_or_default()) as i64
+}
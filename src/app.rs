@@ -49,6 +49,7 @@ pub fn App() -> impl IntoView {
             <main>
                 <Routes fallback=|| "Page not found".into_view()>
                     <Route path=StaticSegment("/") view=RootPage />
+                    <Route path=StaticSegment("/search") view=SearchPage />
                     <Route path=path!("/search/:category") view=SearchPage />
                     <Route path=StaticSegment("/login") view=LoginPage />
                     <Route path=StaticSegment("/signup") view=SignupPage />
@@ -4,8 +4,18 @@ use leptos::prelude::*;
 
 cfg_if! {
     if #[cfg(feature = "ssr")] {
-        use crate::db::backend::PostgreSQLBackend;
+        use crate::{config::types::UTILS_CONFIG, db::backend::PostgreSQLBackend};
         use axum_login::{AuthSession, AuthnBackend};
+
+        pub fn require_admin() -> Result<SessionUser, ServerFnError> {
+            let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+            let admins = &UTILS_CONFIG.get().expect("utils config set").admins;
+
+            session
+                .user
+                .filter(|user| admins.contains(&user.username))
+                .ok_or_else(|| ServerFnError::ServerError("Admin access required".to_string()))
+        }
     }
 }
 
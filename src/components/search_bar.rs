@@ -1,32 +1,280 @@
+use crate::{
+    apis::model::{SearchCategory, Suggestion},
+    config::{
+        consts::{
+            RECENT_SEARCHES_LIMIT, SUGGESTIONS_DEBOUNCE_MILLIS, SUGGESTIONS_MIN_QUERY_LENGTH,
+        },
+        contexts::{UserGlobalState, UserGlobalStateStoreFields},
+    },
+    utils::search::search_suggestions,
+};
 use leptos::{ev::KeyboardEvent, prelude::*};
-use leptos_router::{NavigateOptions, hooks::use_navigate};
+use leptos_router::{
+    NavigateOptions,
+    hooks::{use_navigate, use_params_map},
+};
+use reactive_stores::Store;
+use std::time::Duration;
 use urlencoding::encode;
 
 #[component]
 pub fn SearchBar() -> impl IntoView {
     let (search_query, set_search_query) = signal(String::new());
+    let (debounced_query, set_debounced_query) = signal(String::new());
+    let (is_open, set_is_open) = signal(false);
+    let (highlighted, set_highlighted) = signal(None::<usize>);
+    let debounce = StoredValue::new(None::<TimeoutHandle>);
+    let user_state = expect_context::<Store<UserGlobalState>>();
     let navigate = use_navigate();
+    let params = use_params_map();
+    let category = move || {
+        params
+            .read()
+            .get("category")
+            .and_then(|category| SearchCategory::from_name(&category))
+            .unwrap_or_default()
+    };
+
+    let suggestions = Resource::new(
+        move || debounced_query.get(),
+        |query| async move {
+            if query.chars().count() < SUGGESTIONS_MIN_QUERY_LENGTH {
+                Vec::new()
+            } else {
+                search_suggestions(query).await.unwrap_or_default()
+            }
+        },
+    );
+    let recent_searches = move || {
+        search_query
+            .read()
+            .trim()
+            .is_empty()
+            .then(|| user_state.recent_searches().get())
+            .unwrap_or_default()
+    };
+    // Recent searches and suggestions share one list for keyboard navigation
+    let entries = move || {
+        recent_searches()
+            .into_iter()
+            .map(|query| {
+                let href = format!("/search/{}?q={}", category().as_str(), encode(&query));
+                (query, href)
+            })
+            .chain(
+                suggestions
+                    .get()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|suggestion| (suggestion.title.clone(), suggestion.href())),
+            )
+            .collect::<Vec<_>>()
+    };
+
+    let go = move |query: String, href: String| {
+        user_state.recent_searches().update(|recent| {
+            recent.retain(|item| *item != query);
+            recent.insert(0, query);
+            recent.truncate(RECENT_SEARCHES_LIMIT);
+        });
+        set_is_open.set(false);
+        set_highlighted.set(None);
+        navigate(&href, NavigateOptions::default());
+    };
+    let on_input = move |query: String| {
+        set_search_query.set(query.clone());
+        set_highlighted.set(None);
+        set_is_open.set(true);
+        if let Some(handle) = debounce.get_value() {
+            handle.clear();
+        }
+        debounce.set_value(
+            set_timeout_with_handle(
+                move || set_debounced_query.set(query.trim().to_string()),
+                Duration::from_millis(SUGGESTIONS_DEBOUNCE_MILLIS),
+            )
+            .ok(),
+        );
+    };
+    let on_keydown = {
+        let go = go.clone();
+        move |ev: KeyboardEvent| {
+            let count = entries().len();
+            match ev.key().as_str() {
+                "ArrowDown" if count > 0 => {
+                    ev.prevent_default();
+                    set_is_open.set(true);
+                    set_highlighted.update(|index| {
+                        *index = Some(index.map_or(0, |index| (index + 1) % count));
+                    });
+                }
+                "ArrowUp" if count > 0 => {
+                    ev.prevent_default();
+                    set_highlighted.update(|index| {
+                        *index = Some(index.map_or(count - 1, |index| (index + count - 1) % count));
+                    });
+                }
+                "Escape" => {
+                    set_is_open.set(false);
+                    set_highlighted.set(None);
+                }
+                "Enter" => {
+                    if let Some((query, href)) = highlighted
+                        .get()
+                        .and_then(|index| entries().into_iter().nth(index))
+                    {
+                        go(query, href);
+                    } else {
+                        let query = search_query.get().trim().to_string();
+                        if !query.is_empty() {
+                            let href =
+                                format!("/search/{}?q={}", category().as_str(), encode(&query));
+                            go(query, href);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    };
 
     view! {
-        <div class="p-2 w-full md:w-auto">
+        <div class="relative p-2 w-full md:w-auto">
             <input
                 type="text"
                 placeholder="Search animanga..."
+                title="Narrow results with genre:, -genre:, year:, season:, status:, type:, rating:, score: and sort:"
                 class="w-full input input-sm input-bordered text-base-content text-primary md:input-md"
+                autocomplete="off"
+                role="combobox"
+                aria-expanded=move || is_open.get().to_string()
                 prop:value=search_query
-                on:input=move |ev| set_search_query.set(event_target_value(&ev))
-                on:keypress=move |ev: KeyboardEvent| {
-                    if ev.key() == "Enter" {
-                        let query = move || search_query.get();
-                        if !query().is_empty() {
-                            navigate(
-                                &format!("/search/anime?q={}", encode(&query())),
-                                NavigateOptions::default(),
-                            );
-                        }
-                    }
-                }
+                on:input=move |ev| on_input(event_target_value(&ev))
+                on:keydown=on_keydown
+                on:focus=move |_| set_is_open.set(true)
+                on:blur=move |_| set_is_open.set(false)
             />
+            <Show when=move || is_open.get() && !entries().is_empty()>
+                <ul
+                    role="listbox"
+                    class="overflow-y-auto absolute right-2 left-2 z-50 mt-1 max-h-96 shadow-lg menu bg-base-100 text-base-content rounded-box"
+                >
+                    {move || {
+                        let recent = recent_searches();
+                        let offset = recent.len();
+                        let recent = recent
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, query)| {
+                                let go = go.clone();
+                                let href = format!(
+                                    "/search/{}?q={}",
+                                    category().as_str(),
+                                    encode(&query),
+                                );
+                                view! {
+                                    <li>
+                                        <a
+                                            class:active=move || highlighted.get() == Some(index)
+                                            on:mousedown=move |ev| {
+                                                ev.prevent_default();
+                                                go(query.clone(), href.clone());
+                                            }
+                                        >
+                                            <span class="opacity-60">"Recent"</span>
+                                            {query.clone()}
+                                        </a>
+                                    </li>
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        let matches = suggestions
+                            .get()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, suggestion)| {
+                                view! {
+                                    <SuggestionItem
+                                        suggestion
+                                        query=debounced_query.get()
+                                        active=Signal::derive(move || {
+                                            highlighted.get() == Some(offset + index)
+                                        })
+                                        go=go.clone()
+                                    />
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        view! {
+                            {recent}
+                            {matches}
+                        }
+                    }}
+                </ul>
+            </Show>
         </div>
     }
 }
+
+#[component]
+fn SuggestionItem(
+    suggestion: Suggestion,
+    query: String,
+    active: Signal<bool>,
+    go: impl Fn(String, String) + 'static,
+) -> impl IntoView {
+    let href = suggestion.href();
+    let (before, matched, after) = split_match(&suggestion.title, &query);
+    let title = suggestion.title.clone();
+
+    view! {
+        <li>
+            <a
+                class="flex gap-2 items-center"
+                class:active=active
+                on:mousedown=move |ev| {
+                    ev.prevent_default();
+                    go(title.clone(), href.clone());
+                }
+            >
+                <img
+                    src=suggestion.image_url
+                    alt=""
+                    class="object-cover w-8 h-12 rounded shrink-0"
+                    loading="lazy"
+                />
+                <span class="flex-1 truncate">
+                    {before} <mark class="bg-transparent font-bold text-primary">{matched}</mark>
+                    {after}
+                </span>
+                <span class="badge badge-ghost badge-sm">{suggestion.category.as_str()}</span>
+            </a>
+        </li>
+    }
+}
+
+fn split_match(title: &str, query: &str) -> (String, String, String) {
+    // Lowercasing can change byte lengths outside ASCII, so only trust the folded
+    // offsets when they still line up with the original title
+    let folded = title.to_lowercase();
+    let start = if folded.len() == title.len() {
+        folded.find(&query.to_lowercase())
+    } else {
+        title.find(query)
+    };
+    match start {
+        Some(start)
+            if !query.is_empty()
+                && title.is_char_boundary(start)
+                && title.is_char_boundary(start + query.len()) =>
+        {
+            (
+                title[..start].to_string(),
+                title[start..start + query.len()].to_string(),
+                title[start + query.len()..].to_string(),
+            )
+        }
+        _ => (title.to_string(), String::new(), String::new()),
+    }
+}
@@ -5,3 +5,65 @@ pub const USERNAME_LENGTH_MINIMUM: usize = 2;
 pub const USERNAME_LENGTH_MAXIMUM: usize = 128;
 
 pub const TOASTS_TIMEOUT: u64 = 3;
+
+pub const SCRAPE_MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+pub const SCRAPE_RETRY_BASE_SECONDS: u64 = 60;
+
+pub const INDEXER_BATCH_SIZE: i64 = 500;
+
+pub const REINDEX_TASK_TIMEOUT_SECONDS: u64 = 3600;
+
+pub const SEARCH_PAGE_SIZE: usize = 25;
+
+pub const SUGGESTIONS_PER_CATEGORY: usize = 4;
+
+pub const SUGGESTIONS_DEBOUNCE_MILLIS: u64 = 250;
+
+pub const SUGGESTIONS_MIN_QUERY_LENGTH: usize = 2;
+
+pub const RECENT_SEARCHES_LIMIT: usize = 5;
+
+pub const POSTGRES_SEARCH_CANDIDATES: usize = 500;
+
+pub const SNIPPET_CROP_WORDS: usize = 24;
+
+pub const MAX_HIGHLIGHTS: usize = 2;
+
+pub const SEARCH_CACHE_CAPACITY: u64 = 10_000;
+
+pub const SEARCH_CACHE_TTL_SECONDS: u64 = 300;
+
+pub const CATALOG_CACHE_CAPACITY: u64 = 10_000;
+
+pub const CATALOG_CACHE_TTL_SECONDS: u64 = 3600;
+
+pub const PROVIDER_CACHE_CAPACITY: u64 = 10_000;
+
+pub const PROVIDER_CACHE_TTL_SECONDS: u64 = 3600;
+
+pub const VOCABULARY_TTL_SECONDS: u64 = 86_400;
+
+pub const DID_YOU_MEAN_THRESHOLD: usize = 3;
+
+pub const DID_YOU_MEAN_LIMIT: usize = 3;
+
+pub const DID_YOU_MEAN_MIN_TOKEN_LENGTH: usize = 4;
+
+pub const TRENDING_WINDOW_DAYS: i32 = 7;
+
+pub const TRENDING_SEARCHES_LIMIT: i64 = 10;
+
+pub const TRENDING_MIN_SEARCHES: i64 = 3;
+
+pub const ZERO_RESULT_REPORT_DAYS: i32 = 30;
+
+pub const ZERO_RESULT_REPORT_LIMIT: i64 = 100;
+
+pub const SEARCH_EVENT_PURGE_INTERVAL_SECONDS: u64 = 3600;
+
+pub const SEARCH_EVENT_RESULT_KEY_MAX_LENGTH: usize = 64;
+
+pub const SEARCH_SESSION_EVENTS_KEY: &str = "search_events";
+
+pub const SEARCH_SESSION_EVENTS_MAX: usize = 20;
@@ -8,4 +8,6 @@ pub struct UserGlobalState {
     pub user: Option<SessionUser>,
     pub is_authenticated: bool,
     pub theme_preference: Option<Theme>,
+    #[serde(default)]
+    pub recent_searches: Vec<String>,
 }
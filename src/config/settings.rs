@@ -22,6 +22,8 @@ pub struct AuthConfig {
     pub session_cleanup_interval_seconds: u64,
     pub session_timeout_seconds: i64,
     pub table_name: String,
+    #[serde(default)]
+    pub admins: Vec<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -30,7 +32,71 @@ pub struct MeilisearchConfig {
     pub master_key: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub backend: SearchBackendKind,
+    pub meilisearch_timeout_ms: u64,
+    pub analytics_retention_days: i32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            backend: SearchBackendKind::default(),
+            meilisearch_timeout_ms: 2000,
+            analytics_retention_days: 90,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchBackendKind {
+    #[default]
+    Meilisearch,
+    Postgres,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct APISConfig {
     pub fetch_interval_hours: u64,
+    pub full_sync_interval_hours: u64,
+    pub jikan_requests_per_second: u32,
+    pub jikan_requests_per_minute: u32,
+    pub anilist_requests_per_minute: u32,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub jikan_base_url: String,
+    pub anilist_url: String,
+    pub http_mode: HttpMode,
+    pub fixture_dir: String,
+}
+
+impl Default for APISConfig {
+    fn default() -> Self {
+        Self {
+            fetch_interval_hours: 24,
+            full_sync_interval_hours: 168,
+            jikan_requests_per_second: 3,
+            jikan_requests_per_minute: 60,
+            anilist_requests_per_minute: 90,
+            max_retries: 5,
+            retry_base_delay_ms: 1000,
+            jikan_base_url: "https://api.jikan.moe/v4".to_string(),
+            anilist_url: "https://graphql.anilist.co/".to_string(),
+            http_mode: HttpMode::default(),
+            fixture_dir: "fixtures".to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpMode {
+    #[default]
+    Live,
+    Record,
+    Replay,
 }
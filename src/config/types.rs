@@ -1,4 +1,7 @@
-use crate::config::settings::MainConfig;
+use crate::{
+    apis::model::{Entity, Provider, ScraperStatus, canonical_index_name, index_name},
+    config::settings::{APISConfig, MainConfig, SearchConfig},
+};
 
 use axum::extract::FromRef;
 use fastrand::Rng;
@@ -8,8 +11,8 @@ use meilisearch_sdk::client::Client as MClient;
 use once_cell::sync::{Lazy, OnceCell};
 use reqwest::Client as RClient;
 use sqlx::{Pool, Postgres};
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::{Mutex, Notify};
 
 #[derive(Clone, FromRef)]
 pub struct AppState {
@@ -21,9 +24,34 @@ pub struct AppState {
 #[derive(Debug)]
 pub struct UtilsConfig {
     pub main: MainConfig,
+    pub admins: Vec<String>,
+    pub apis: APISConfig,
+    pub search: SearchConfig,
 }
 
 pub static UTILS_CONFIG: OnceCell<Arc<UtilsConfig>> = OnceCell::new();
 pub static HTTP_CLIENT: Lazy<RClient> = Lazy::new(RClient::new);
 pub static MEILISEARCH_CLIENT: OnceCell<Arc<MClient>> = OnceCell::new();
 pub static RNG: Lazy<Mutex<Rng>> = Lazy::new(|| Mutex::new(Rng::new()));
+pub static SCRAPER_STATUS: Lazy<Mutex<HashMap<(Provider, Entity), ScraperStatus>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+pub static SCRAPER_TRIGGERS: Lazy<HashMap<(Provider, Entity), Notify>> = Lazy::new(|| {
+    Provider::ALL
+        .into_iter()
+        .flat_map(|provider| Entity::ALL.map(|entity| ((provider, entity), Notify::new())))
+        .collect()
+});
+// Held while writing to or rebuilding an index, so a rebuild's swap never races another
+// rebuild or drops documents pushed to the index it replaces
+pub static INDEX_LOCKS: Lazy<HashMap<String, Mutex<()>>> = Lazy::new(|| {
+    Entity::ALL
+        .into_iter()
+        .flat_map(|entity| {
+            Provider::ALL
+                .map(|provider| index_name(provider, entity))
+                .into_iter()
+                .chain([canonical_index_name(entity).to_string()])
+        })
+        .map(|index| (index, Mutex::new(())))
+        .collect()
+});
@@ -0,0 +1,124 @@
+use crate::{
+    apis::model::{SearchCategory, TrendingSearch, ZeroResultSearch},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+};
+use sqlx::query;
+
+impl PostgreSQLBackend {
+    pub async fn record_search_event(
+        &self,
+        query: &str,
+        category: SearchCategory,
+        filters: &str,
+        result_count: usize,
+    ) -> Result<i64, AppError> {
+        query!(
+            "INSERT INTO search_events (query, category, filters, result_count)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+            query,
+            category.as_str(),
+            filters,
+            i32::try_from(result_count).unwrap_or(i32::MAX),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map(|row| row.id)
+        .map_err(|e| AppError::DatabaseError(format!("Record search event: {e}")))
+    }
+
+    // Only the first click of a recent event counts, so replayed requests can't rewrite
+    // older events
+    pub async fn record_search_click(&self, event_id: i64, result: &str) -> Result<(), AppError> {
+        query!(
+            "UPDATE search_events SET clicked_result = $2
+             WHERE id = $1 AND clicked_result IS NULL AND created_at > NOW() - INTERVAL '1 hour'",
+            event_id,
+            result,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Record search click: {e}")))?;
+
+        Ok(())
+    }
+
+    pub async fn trending_searches(
+        &self,
+        window_days: i32,
+        min_searches: i64,
+        limit: i64,
+    ) -> Result<Vec<TrendingSearch>, AppError> {
+        let rows = query!(
+            r#"SELECT query, category, COUNT(*) AS "searches!"
+               FROM search_events
+               WHERE created_at > NOW() - make_interval(days => $1) AND result_count > 0
+               GROUP BY query, category
+               HAVING COUNT(*) >= $2
+               ORDER BY COUNT(*) DESC, query
+               LIMIT $3"#,
+            window_days,
+            min_searches,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch trending searches: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(TrendingSearch {
+                    category: SearchCategory::from_name(&row.category)?,
+                    query: row.query,
+                    searches: row.searches,
+                })
+            })
+            .collect())
+    }
+
+    pub async fn zero_result_searches(
+        &self,
+        window_days: i32,
+        limit: i64,
+    ) -> Result<Vec<ZeroResultSearch>, AppError> {
+        let rows = query!(
+            r#"SELECT query, category, filters, COUNT(*) AS "searches!",
+                   MAX(created_at) AS "last_searched!"
+               FROM search_events
+               WHERE created_at > NOW() - make_interval(days => $1) AND result_count = 0
+               GROUP BY query, category, filters
+               ORDER BY COUNT(*) DESC, MAX(created_at) DESC
+               LIMIT $2"#,
+            window_days,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch zero-result searches: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                Some(ZeroResultSearch {
+                    category: SearchCategory::from_name(&row.category)?,
+                    query: row.query,
+                    filters: row.filters,
+                    searches: row.searches,
+                    last_searched: row.last_searched.unix_timestamp(),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn purge_search_events(&self, retention_days: i32) -> Result<u64, AppError> {
+        query!(
+            "DELETE FROM search_events WHERE created_at < NOW() - make_interval(days => $1)",
+            retention_days,
+        )
+        .execute(&self.pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(|e| AppError::DatabaseError(format!("Purge search events: {e}")))
+    }
+}
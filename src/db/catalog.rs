@@ -0,0 +1,125 @@
+use crate::{
+    apis::model::{Entity, Provider},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+};
+use sqlx::{query, query_as, query_scalar};
+use std::collections::HashSet;
+use time::OffsetDateTime;
+
+pub struct SyncState {
+    pub last_incremental_sync: Option<OffsetDateTime>,
+    pub last_full_sync: Option<OffsetDateTime>,
+    pub known_id: Option<i32>,
+}
+
+struct SqlSyncState {
+    last_incremental_sync: Option<OffsetDateTime>,
+    last_full_sync: Option<OffsetDateTime>,
+}
+
+impl PostgreSQLBackend {
+    pub async fn sync_state(
+        &self,
+        provider: Provider,
+        entity: Entity,
+    ) -> Result<SyncState, AppError> {
+        let state = query_as!(
+            SqlSyncState,
+            "SELECT last_incremental_sync, last_full_sync FROM catalog_sync_state
+             WHERE provider = $1 AND entity = $2",
+            provider.as_str(),
+            entity.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch sync state: {e}")))?;
+
+        let known_id = query_scalar!(
+            "SELECT MAX(id) FROM catalog_fingerprints WHERE provider = $1 AND entity = $2",
+            provider.as_str(),
+            entity.as_str(),
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch known id: {e}")))?;
+
+        Ok(SyncState {
+            last_incremental_sync: state.as_ref().and_then(|s| s.last_incremental_sync),
+            last_full_sync: state.and_then(|s| s.last_full_sync),
+            known_id,
+        })
+    }
+
+    pub async fn record_sync(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        full_sync: bool,
+    ) -> Result<(), AppError> {
+        query!(
+            "INSERT INTO catalog_sync_state (provider, entity, last_incremental_sync, last_full_sync)
+             VALUES ($1, $2, NOW(), CASE WHEN $3 THEN NOW() END)
+             ON CONFLICT (provider, entity) DO UPDATE SET
+                 last_incremental_sync = EXCLUDED.last_incremental_sync,
+                 last_full_sync = COALESCE(EXCLUDED.last_full_sync, catalog_sync_state.last_full_sync)",
+            provider.as_str(),
+            entity.as_str(),
+            full_sync,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Record sync: {e}")))?;
+
+        Ok(())
+    }
+
+    pub async fn changed_documents(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        ids: &[i32],
+        fingerprints: &[i64],
+    ) -> Result<HashSet<i32>, AppError> {
+        let changed = query_scalar!(
+            r#"SELECT input.id AS "id!" FROM UNNEST($3::int4[], $4::int8[]) AS input(id, fingerprint)
+               LEFT JOIN catalog_fingerprints f
+                   ON f.provider = $1 AND f.entity = $2 AND f.id = input.id
+               WHERE f.fingerprint IS DISTINCT FROM input.fingerprint"#,
+            provider.as_str(),
+            entity.as_str(),
+            ids,
+            fingerprints,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Compare fingerprints: {e}")))?;
+
+        Ok(changed.into_iter().collect())
+    }
+
+    pub async fn store_fingerprints(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        ids: &[i32],
+        fingerprints: &[i64],
+    ) -> Result<(), AppError> {
+        query!(
+            "INSERT INTO catalog_fingerprints (provider, entity, id, fingerprint)
+             SELECT $1::text, $2::text, * FROM UNNEST($3::int4[], $4::int8[])
+             ON CONFLICT (provider, entity, id) DO UPDATE SET
+                 fingerprint = EXCLUDED.fingerprint,
+                 synced_at = NOW()",
+            provider.as_str(),
+            entity.as_str(),
+            ids,
+            fingerprints,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store fingerprints: {e}")))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    apis::model::{CatalogIdentity, Entity, Provider, ProviderId},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+};
+use sqlx::{query, query_scalar};
+use std::collections::BTreeMap;
+
+/// A provider id that was already linked to another work than the document listing it
+pub struct LinkConflict {
+    pub id: ProviderId,
+    pub linked_to: i64,
+    pub expected: i64,
+}
+
+impl PostgreSQLBackend {
+    pub async fn link_ids(
+        &self,
+        entity: Entity,
+        documents: &[Vec<ProviderId>],
+    ) -> Result<Vec<LinkConflict>, AppError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Begin linking ids: {e}")))?;
+        // Crawls of different providers link the same works, so without the lock both could
+        // miss each other's canonical id and create one each
+        query!(
+            r#"SELECT 1 AS "locked!" FROM pg_advisory_xact_lock(hashtext('provider_ids:' || $1))"#,
+            entity.as_str(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Lock linked ids: {e}")))?;
+
+        let mut conflicts = Vec::new();
+
+        for ids in documents {
+            let Some(own) = ids.first() else {
+                continue;
+            };
+            let (providers, provider_ids): (Vec<&str>, Vec<i32>) =
+                ids.iter().map(|id| (id.provider.as_str(), id.id)).unzip();
+
+            let existing = query_scalar!(
+                "SELECT canonical_id FROM provider_ids
+                 WHERE entity = $1 AND (provider, provider_id) IN (
+                     SELECT * FROM UNNEST($2::text[], $3::int4[])
+                 )
+                 ORDER BY provider = $4 AND provider_id = $5 DESC
+                 LIMIT 1",
+                entity.as_str(),
+                &providers as &[&str],
+                &provider_ids,
+                own.provider.as_str(),
+                own.id,
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Find canonical id: {e}")))?;
+
+            let canonical_id = match existing {
+                Some(canonical_id) => canonical_id,
+                None => query_scalar!(
+                    "INSERT INTO canonical_ids (entity) VALUES ($1) RETURNING id",
+                    entity.as_str(),
+                )
+                .fetch_one(&mut *tx)
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Create canonical id: {e}")))?,
+            };
+
+            query!(
+                "INSERT INTO provider_ids (entity, provider, provider_id, canonical_id)
+                 SELECT $1::text, provider, provider_id, $4::int8
+                 FROM UNNEST($2::text[], $3::int4[]) AS input(provider, provider_id)
+                 ON CONFLICT (provider, entity, provider_id) DO NOTHING",
+                entity.as_str(),
+                &providers as &[&str],
+                &provider_ids,
+                canonical_id,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Link provider ids: {e}")))?;
+
+            let linked_elsewhere = query!(
+                "SELECT provider, provider_id, canonical_id FROM provider_ids
+                 WHERE entity = $1 AND canonical_id <> $4 AND (provider, provider_id) IN (
+                     SELECT * FROM UNNEST($2::text[], $3::int4[])
+                 )",
+                entity.as_str(),
+                &providers as &[&str],
+                &provider_ids,
+                canonical_id,
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Check linked ids: {e}")))?;
+            conflicts.extend(linked_elsewhere.into_iter().filter_map(|row| {
+                Some(LinkConflict {
+                    id: ProviderId {
+                        provider: Provider::from_name(&row.provider)?,
+                        id: row.provider_id,
+                    },
+                    linked_to: row.canonical_id,
+                    expected: canonical_id,
+                })
+            }));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Commit linked ids: {e}")))?;
+
+        Ok(conflicts)
+    }
+
+    pub async fn canonical_id(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        id: i32,
+    ) -> Result<Option<i64>, AppError> {
+        query_scalar!(
+            "SELECT canonical_id FROM provider_ids
+             WHERE provider = $1 AND entity = $2 AND provider_id = $3",
+            provider.as_str(),
+            entity.as_str(),
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch canonical id: {e}")))
+    }
+
+    pub async fn catalog_identity(
+        &self,
+        canonical_id: i64,
+    ) -> Result<Option<CatalogIdentity>, AppError> {
+        let rows = query!(
+            "SELECT c.entity, p.provider, p.provider_id FROM canonical_ids c
+             JOIN provider_ids p ON p.canonical_id = c.id
+             WHERE c.id = $1
+             ORDER BY p.provider",
+            canonical_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch provider ids: {e}")))?;
+
+        let Some(entity) = rows.first().and_then(|row| Entity::from_name(&row.entity)) else {
+            return Ok(None);
+        };
+
+        Ok(Some(CatalogIdentity {
+            canonical_id,
+            entity,
+            ids: rows
+                .into_iter()
+                .filter_map(|row| {
+                    Some(ProviderId {
+                        provider: Provider::from_name(&row.provider)?,
+                        id: row.provider_id,
+                    })
+                })
+                .collect(),
+        }))
+    }
+
+    pub async fn linked_identities(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        ids: &[i32],
+    ) -> Result<Vec<CatalogIdentity>, AppError> {
+        let rows = query!(
+            "SELECT linked.canonical_id, linked.provider, linked.provider_id FROM provider_ids own
+             JOIN provider_ids linked ON linked.canonical_id = own.canonical_id
+             WHERE own.provider = $1 AND own.entity = $2 AND own.provider_id = ANY($3)",
+            provider.as_str(),
+            entity.as_str(),
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch linked ids: {e}")))?;
+
+        Ok(group_identities(
+            entity,
+            rows.into_iter()
+                .map(|row| (row.canonical_id, row.provider, row.provider_id)),
+        ))
+    }
+
+    pub async fn identities_after(
+        &self,
+        entity: Entity,
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<CatalogIdentity>, AppError> {
+        let rows = query!(
+            "SELECT p.canonical_id, p.provider, p.provider_id FROM provider_ids p
+             WHERE p.canonical_id IN (
+                 SELECT id FROM canonical_ids WHERE entity = $1 AND id > $2 ORDER BY id LIMIT $3
+             )",
+            entity.as_str(),
+            after,
+            limit,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch canonical ids: {e}")))?;
+
+        Ok(group_identities(
+            entity,
+            rows.into_iter()
+                .map(|row| (row.canonical_id, row.provider, row.provider_id)),
+        ))
+    }
+
+    pub async fn identities_of(
+        &self,
+        entity: Entity,
+        canonical_ids: &[i64],
+    ) -> Result<Vec<CatalogIdentity>, AppError> {
+        let rows = query!(
+            "SELECT canonical_id, provider, provider_id FROM provider_ids
+             WHERE entity = $1 AND canonical_id = ANY($2)",
+            entity.as_str(),
+            canonical_ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch canonical ids: {e}")))?;
+
+        Ok(group_identities(
+            entity,
+            rows.into_iter()
+                .map(|row| (row.canonical_id, row.provider, row.provider_id)),
+        ))
+    }
+}
+
+fn group_identities(
+    entity: Entity,
+    rows: impl Iterator<Item = (i64, String, i32)>,
+) -> Vec<CatalogIdentity> {
+    let mut identities: BTreeMap<i64, Vec<ProviderId>> = BTreeMap::new();
+    for (canonical_id, provider, id) in rows {
+        if let Some(provider) = Provider::from_name(&provider) {
+            identities
+                .entry(canonical_id)
+                .or_default()
+                .push(ProviderId { provider, id });
+        }
+    }
+
+    identities
+        .into_iter()
+        .map(|(canonical_id, ids)| CatalogIdentity {
+            canonical_id,
+            entity,
+            ids,
+        })
+        .collect()
+}
@@ -1 +1,7 @@
+pub mod analytics;
 pub mod backend;
+pub mod catalog;
+pub mod identity;
+pub mod records;
+pub mod scrape;
+pub mod search;
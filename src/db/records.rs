@@ -0,0 +1,497 @@
+use crate::{
+    apis::model::{Character, Entity, Media, MediaKind, MediaTitle, Provider},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+    utils::normalize::search_forms,
+};
+use sqlx::{query, query_as, query_scalar};
+use std::collections::HashMap;
+
+struct SqlMedia {
+    id: i32,
+    mal_id: Option<i32>,
+    url: String,
+    image_url: Option<String>,
+    media_type: Option<String>,
+    status: Option<String>,
+    episodes: Option<i32>,
+    chapters: Option<i32>,
+    volumes: Option<i32>,
+    score: Option<f32>,
+    rank: Option<i32>,
+    popularity: Option<i32>,
+    favorites: Option<i32>,
+    synopsis: Option<String>,
+    season: Option<String>,
+    year: Option<i32>,
+    rating: Option<String>,
+}
+
+struct SqlCharacter {
+    id: i32,
+    url: String,
+    image_url: Option<String>,
+    name: String,
+    name_native: Option<String>,
+    favorites: Option<i32>,
+    about: Option<String>,
+}
+
+const GENRE: &str = "genre";
+const TAG: &str = "tag";
+const CREATOR: &str = "creator";
+const SYNONYM: &str = "synonym";
+
+impl PostgreSQLBackend {
+    pub async fn store_media(
+        &self,
+        provider: Provider,
+        kind: MediaKind,
+        media: &[Media],
+    ) -> Result<(), AppError> {
+        let kind_name = Entity::from(kind).as_str();
+        let ids: Vec<i32> = media.iter().map(|m| m.id).collect();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Begin storing media: {e}")))?;
+
+        query!(
+            "INSERT INTO catalog_media (
+                 provider, kind, id, mal_id, url, image_url, media_type, status, episodes, chapters,
+                 volumes, score, rank, popularity, favorites, synopsis, season, year, rating
+             )
+             SELECT $1::text, $2::text, * FROM UNNEST(
+                 $3::int4[], $4::int4[], $5::text[], $6::text[], $7::text[], $8::text[],
+                 $9::int4[], $10::int4[], $11::int4[], $12::float4[], $13::int4[], $14::int4[],
+                 $15::int4[], $16::text[], $17::text[], $18::int4[], $19::text[]
+             )
+             ON CONFLICT (provider, kind, id) DO UPDATE SET
+                 mal_id = EXCLUDED.mal_id,
+                 url = EXCLUDED.url,
+                 image_url = EXCLUDED.image_url,
+                 media_type = EXCLUDED.media_type,
+                 status = EXCLUDED.status,
+                 episodes = EXCLUDED.episodes,
+                 chapters = EXCLUDED.chapters,
+                 volumes = EXCLUDED.volumes,
+                 score = EXCLUDED.score,
+                 rank = EXCLUDED.rank,
+                 popularity = EXCLUDED.popularity,
+                 favorites = EXCLUDED.favorites,
+                 synopsis = EXCLUDED.synopsis,
+                 season = EXCLUDED.season,
+                 year = EXCLUDED.year,
+                 rating = EXCLUDED.rating,
+                 updated_at = NOW()",
+            provider.as_str(),
+            kind_name,
+            &ids,
+            &media.iter().map(|m| m.mal_id).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.url.clone()).collect::<Vec<_>>(),
+            &media
+                .iter()
+                .map(|m| m.image_url.clone())
+                .collect::<Vec<_>>() as &[Option<String>],
+            &media
+                .iter()
+                .map(|m| m.media_type.clone())
+                .collect::<Vec<_>>() as &[Option<String>],
+            &media.iter().map(|m| m.status.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &media.iter().map(|m| m.episodes).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.chapters).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.volumes).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.score).collect::<Vec<_>>() as &[Option<f32>],
+            &media.iter().map(|m| m.rank).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.popularity).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.favorites).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.synopsis.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &media.iter().map(|m| m.season.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &media.iter().map(|m| m.year).collect::<Vec<_>>() as &[Option<i32>],
+            &media.iter().map(|m| m.rating.clone()).collect::<Vec<_>>() as &[Option<String>],
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store media: {e}")))?;
+
+        query!(
+            "DELETE FROM catalog_media_titles WHERE provider = $1 AND kind = $2 AND media_id = ANY($3)",
+            provider.as_str(),
+            kind_name,
+            &ids,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Clear media titles: {e}")))?;
+
+        let (mut media_ids, mut positions, mut title_types, mut titles, mut normalized) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for m in media {
+            for (position, title) in (0_i32..).zip(&m.titles) {
+                media_ids.push(m.id);
+                positions.push(position);
+                title_types.push(title.title_type.clone());
+                titles.push(title.title.clone());
+                normalized.push(unspaced_forms(&title.title));
+            }
+        }
+        query!(
+            "INSERT INTO catalog_media_titles (provider, kind, media_id, position, title_type, title, normalized)
+             SELECT $1::text, $2::text, * FROM UNNEST($3::int4[], $4::int4[], $5::text[], $6::text[], $7::text[])",
+            provider.as_str(),
+            kind_name,
+            &media_ids,
+            &positions,
+            &title_types,
+            &titles,
+            &normalized,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store media titles: {e}")))?;
+
+        query!(
+            "DELETE FROM catalog_media_terms WHERE provider = $1 AND kind = $2 AND media_id = ANY($3)",
+            provider.as_str(),
+            kind_name,
+            &ids,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Clear media terms: {e}")))?;
+
+        let (mut media_ids, mut categories, mut positions, mut names) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for m in media {
+            for (category, terms) in [
+                (GENRE, &m.genres),
+                (TAG, &m.tags),
+                (CREATOR, &m.creators),
+                (SYNONYM, &m.synonyms),
+            ] {
+                for (position, name) in (0_i32..).zip(terms) {
+                    media_ids.push(m.id);
+                    categories.push(category);
+                    positions.push(position);
+                    names.push(name.clone());
+                }
+            }
+        }
+        query!(
+            "INSERT INTO catalog_media_terms (provider, kind, media_id, category, position, name)
+             SELECT $1::text, $2::text, * FROM UNNEST($3::int4[], $4::text[], $5::int4[], $6::text[])",
+            provider.as_str(),
+            kind_name,
+            &media_ids,
+            &categories as &[&str],
+            &positions,
+            &names,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store media terms: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Commit media: {e}")))
+    }
+
+    pub async fn load_media(
+        &self,
+        provider: Provider,
+        kind: MediaKind,
+        ids: &[i32],
+    ) -> Result<Vec<Media>, AppError> {
+        let kind_name = Entity::from(kind).as_str();
+        let rows = query_as!(
+            SqlMedia,
+            "SELECT id, mal_id, url, image_url, media_type, status, episodes, chapters, volumes,
+                    score, rank, popularity, favorites, synopsis, season, year, rating
+             FROM catalog_media WHERE provider = $1 AND kind = $2 AND id = ANY($3)
+             ORDER BY id",
+            provider.as_str(),
+            kind_name,
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load media: {e}")))?;
+
+        let mut titles: HashMap<i32, Vec<MediaTitle>> = HashMap::new();
+        for row in query!(
+            "SELECT media_id, title_type, title FROM catalog_media_titles
+             WHERE provider = $1 AND kind = $2 AND media_id = ANY($3)
+             ORDER BY media_id, position",
+            provider.as_str(),
+            kind_name,
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load media titles: {e}")))?
+        {
+            titles.entry(row.media_id).or_default().push(MediaTitle {
+                title_type: row.title_type,
+                title: row.title,
+            });
+        }
+
+        let mut terms: HashMap<(i32, String), Vec<String>> = HashMap::new();
+        for row in query!(
+            "SELECT media_id, category, name FROM catalog_media_terms
+             WHERE provider = $1 AND kind = $2 AND media_id = ANY($3)
+             ORDER BY media_id, category, position",
+            provider.as_str(),
+            kind_name,
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load media terms: {e}")))?
+        {
+            terms
+                .entry((row.media_id, row.category))
+                .or_default()
+                .push(row.name);
+        }
+        let mut take_terms = |id: i32, category: &str| {
+            terms
+                .remove(&(id, category.to_string()))
+                .unwrap_or_default()
+        };
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Media {
+                id: row.id,
+                provider,
+                kind,
+                mal_id: row.mal_id,
+                url: row.url,
+                image_url: row.image_url,
+                titles: titles.remove(&row.id).unwrap_or_default(),
+                synonyms: take_terms(row.id, SYNONYM),
+                media_type: row.media_type,
+                status: row.status,
+                rating: row.rating,
+                episodes: row.episodes,
+                chapters: row.chapters,
+                volumes: row.volumes,
+                score: row.score,
+                rank: row.rank,
+                popularity: row.popularity,
+                favorites: row.favorites,
+                synopsis: row.synopsis,
+                season: row.season,
+                year: row.year,
+                genres: take_terms(row.id, GENRE),
+                tags: take_terms(row.id, TAG),
+                creators: take_terms(row.id, CREATOR),
+            })
+            .collect())
+    }
+
+    pub async fn store_characters(
+        &self,
+        provider: Provider,
+        characters: &[Character],
+    ) -> Result<(), AppError> {
+        let ids: Vec<i32> = characters.iter().map(|c| c.id).collect();
+        // Both names in one column, like a media title's forms
+        let normalized: Vec<String> = characters
+            .iter()
+            .map(|c| {
+                [Some(c.name.as_str()), c.name_native.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .map(unspaced_forms)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Begin storing characters: {e}")))?;
+
+        query!(
+            "INSERT INTO catalog_characters (provider, id, url, image_url, name, name_native, favorites, about, normalized)
+             SELECT $1::text, * FROM UNNEST(
+                 $2::int4[], $3::text[], $4::text[], $5::text[], $6::text[], $7::int4[], $8::text[],
+                 $9::text[]
+             )
+             ON CONFLICT (provider, id) DO UPDATE SET
+                 url = EXCLUDED.url,
+                 image_url = EXCLUDED.image_url,
+                 name = EXCLUDED.name,
+                 name_native = EXCLUDED.name_native,
+                 favorites = EXCLUDED.favorites,
+                 about = EXCLUDED.about,
+                 normalized = EXCLUDED.normalized,
+                 updated_at = NOW()",
+            provider.as_str(),
+            &ids,
+            &characters.iter().map(|c| c.url.clone()).collect::<Vec<_>>(),
+            &characters.iter().map(|c| c.image_url.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &characters.iter().map(|c| c.name.clone()).collect::<Vec<_>>(),
+            &characters.iter().map(|c| c.name_native.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &characters.iter().map(|c| c.favorites).collect::<Vec<_>>() as &[Option<i32>],
+            &characters.iter().map(|c| c.about.clone()).collect::<Vec<_>>() as &[Option<String>],
+            &normalized,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store characters: {e}")))?;
+
+        query!(
+            "DELETE FROM catalog_character_nicknames WHERE provider = $1 AND character_id = ANY($2)",
+            provider.as_str(),
+            &ids,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Clear character nicknames: {e}")))?;
+
+        let (mut character_ids, mut positions, mut nicknames) =
+            (Vec::new(), Vec::new(), Vec::new());
+        for c in characters {
+            for (position, nickname) in (0_i32..).zip(&c.nicknames) {
+                character_ids.push(c.id);
+                positions.push(position);
+                nicknames.push(nickname.clone());
+            }
+        }
+        query!(
+            "INSERT INTO catalog_character_nicknames (provider, character_id, position, nickname)
+             SELECT $1::text, * FROM UNNEST($2::int4[], $3::int4[], $4::text[])",
+            provider.as_str(),
+            &character_ids,
+            &positions,
+            &nicknames,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Store character nicknames: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Commit characters: {e}")))
+    }
+
+    pub async fn load_characters(
+        &self,
+        provider: Provider,
+        ids: &[i32],
+    ) -> Result<Vec<Character>, AppError> {
+        let rows = query_as!(
+            SqlCharacter,
+            "SELECT id, url, image_url, name, name_native, favorites, about
+             FROM catalog_characters WHERE provider = $1 AND id = ANY($2)
+             ORDER BY id",
+            provider.as_str(),
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load characters: {e}")))?;
+
+        let mut nicknames: HashMap<i32, Vec<String>> = HashMap::new();
+        for row in query!(
+            "SELECT character_id, nickname FROM catalog_character_nicknames
+             WHERE provider = $1 AND character_id = ANY($2)
+             ORDER BY character_id, position",
+            provider.as_str(),
+            ids,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load character nicknames: {e}")))?
+        {
+            nicknames
+                .entry(row.character_id)
+                .or_default()
+                .push(row.nickname);
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| Character {
+                id: row.id,
+                provider,
+                url: row.url,
+                image_url: row.image_url,
+                name: row.name,
+                name_native: row.name_native,
+                nicknames: nicknames.remove(&row.id).unwrap_or_default(),
+                favorites: row.favorites,
+                about: row.about,
+                highlights: Vec::new(),
+            })
+            .collect())
+    }
+
+    pub async fn record_ids_after(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        after: i32,
+        limit: i64,
+    ) -> Result<Vec<i32>, AppError> {
+        match entity.media_kind() {
+            Some(_) => {
+                query_scalar!(
+                    "SELECT id FROM catalog_media WHERE provider = $1 AND kind = $2 AND id > $3
+                 ORDER BY id LIMIT $4",
+                    provider.as_str(),
+                    entity.as_str(),
+                    after,
+                    limit,
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                query_scalar!(
+                    "SELECT id FROM catalog_characters WHERE provider = $1 AND id > $2
+                 ORDER BY id LIMIT $3",
+                    provider.as_str(),
+                    after,
+                    limit,
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+        }
+        .map_err(|e| AppError::DatabaseError(format!("Fetch record ids: {e}")))
+    }
+
+    pub async fn record_count(&self, provider: Provider, entity: Entity) -> Result<i64, AppError> {
+        match entity.media_kind() {
+            Some(_) => query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM catalog_media WHERE provider = $1 AND kind = $2"#,
+                provider.as_str(),
+                entity.as_str(),
+            )
+            .fetch_one(&self.pool)
+            .await,
+            None => query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM catalog_characters WHERE provider = $1"#,
+                provider.as_str(),
+            )
+            .fetch_one(&self.pool)
+            .await,
+        }
+        .map_err(|e| AppError::DatabaseError(format!("Count records: {e}")))
+    }
+}
+
+// Each search form without spaces, since kana queries have no word breaks, separated by one
+fn unspaced_forms(text: &str) -> String {
+    search_forms(text)
+        .iter()
+        .map(|form| form.replace(' ', ""))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
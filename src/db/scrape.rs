@@ -0,0 +1,117 @@
+use crate::{
+    apis::model::{Entity, Provider},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+};
+use sqlx::{query, query_as};
+
+pub struct ScrapeRun {
+    pub id: i64,
+    pub full_sync: bool,
+    pub last_page: i32,
+    pub failed_pages: Vec<i32>,
+}
+
+impl PostgreSQLBackend {
+    pub async fn running_scrape(
+        &self,
+        provider: Provider,
+        entity: Entity,
+    ) -> Result<Option<ScrapeRun>, AppError> {
+        query_as!(
+            ScrapeRun,
+            "SELECT id, full_sync, last_page, failed_pages FROM scrape_runs
+             WHERE provider = $1 AND entity = $2 AND status = 'running'",
+            provider.as_str(),
+            entity.as_str(),
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Fetch running scrape: {e}")))
+    }
+
+    pub async fn start_scrape(
+        &self,
+        provider: Provider,
+        entity: Entity,
+        full_sync: bool,
+    ) -> Result<ScrapeRun, AppError> {
+        query_as!(
+            ScrapeRun,
+            "INSERT INTO scrape_runs (provider, entity, full_sync) VALUES ($1, $2, $3)
+             RETURNING id, full_sync, last_page, failed_pages",
+            provider.as_str(),
+            entity.as_str(),
+            full_sync,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Start scrape: {e}")))
+    }
+
+    pub async fn checkpoint_scrape(&self, run_id: i64, page: i32) -> Result<(), AppError> {
+        query!(
+            "UPDATE scrape_runs SET last_page = $2, updated_at = NOW() WHERE id = $1",
+            run_id,
+            page,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Checkpoint scrape: {e}")))?;
+
+        Ok(())
+    }
+
+    pub async fn fail_scrape_page(&self, run_id: i64, page: i32) -> Result<(), AppError> {
+        query!(
+            "UPDATE scrape_runs SET
+                 last_page = $2,
+                 failed_pages = array_append(array_remove(failed_pages, $2), $2),
+                 updated_at = NOW()
+             WHERE id = $1",
+            run_id,
+            page,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Record failed page: {e}")))?;
+
+        Ok(())
+    }
+
+    pub async fn recover_scrape_page(&self, run_id: i64, page: i32) -> Result<(), AppError> {
+        query!(
+            "UPDATE scrape_runs SET failed_pages = array_remove(failed_pages, $2), updated_at = NOW()
+             WHERE id = $1",
+            run_id,
+            page,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Recover failed page: {e}")))?;
+
+        Ok(())
+    }
+
+    pub async fn finish_scrape(&self, run_id: i64, error: Option<String>) -> Result<(), AppError> {
+        query!(
+            "UPDATE scrape_runs SET
+                 status = CASE
+                     WHEN $2::text IS NOT NULL THEN 'failed'
+                     WHEN cardinality(failed_pages) > 0 THEN 'incomplete'
+                     ELSE 'completed'
+                 END,
+                 error = $2,
+                 updated_at = NOW(),
+                 finished_at = NOW()
+             WHERE id = $1",
+            run_id,
+            error,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Finish scrape: {e}")))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    apis::model::{Entity, MediaKind, Provider},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+    utils::normalize::{fold_long_vowels, normalize},
+};
+use sqlx::{query, query_scalar};
+
+pub struct RankedIds<T> {
+    pub ids: Vec<T>,
+    pub total: usize,
+}
+
+fn like_pattern(query: &str) -> String {
+    format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    )
+}
+
+fn unspaced_forms(search: &str) -> (String, String) {
+    let normalized = normalize(search);
+    let folded = fold_long_vowels(&normalized);
+    (normalized.replace(' ', ""), folded.replace(' ', ""))
+}
+
+impl PostgreSQLBackend {
+    pub async fn search_media_ids(
+        &self,
+        kind: MediaKind,
+        search: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<RankedIds<i64>, AppError> {
+        // Normalized titles are stored without spaces, since kana queries have no word breaks,
+        // and both search forms are matched so long vowels meet whichever way they're spelled
+        let (normalized, folded) = unspaced_forms(search);
+        let rows = query!(
+            r#"WITH matches AS (
+                   SELECT provider, media_id AS id, similarity(title, $2) AS score
+                   FROM catalog_media_titles
+                   WHERE kind = $1 AND ($2 = '' OR title % $2 OR title ILIKE $5)
+                   UNION ALL
+                   SELECT provider, media_id,
+                       GREATEST(similarity(normalized, $6), similarity(normalized, $8)) * 0.9::real
+                   FROM catalog_media_titles
+                   WHERE kind = $1 AND $6 <> '' AND (
+                       normalized % $6 OR normalized ILIKE $7
+                       OR normalized % $8 OR normalized ILIKE $9
+                   )
+                   UNION ALL
+                   SELECT provider, media_id, similarity(name, $2) * 0.8::real
+                   FROM catalog_media_terms
+                   WHERE kind = $1 AND category = 'synonym' AND $2 <> ''
+                       AND (name % $2 OR name ILIKE $5)
+                   UNION ALL
+                   SELECT provider, id, ts_rank(synopsis_search, plainto_tsquery('english', $2)) * 0.5::real
+                   FROM catalog_media
+                   WHERE kind = $1 AND $2 <> ''
+                       AND synopsis_search @@ plainto_tsquery('english', $2)
+               )
+               SELECT p.canonical_id AS "canonical_id!", COUNT(*) OVER () AS "total!"
+               FROM matches m
+               JOIN provider_ids p
+                   ON p.entity = $1 AND p.provider = m.provider AND p.provider_id = m.id
+               GROUP BY p.canonical_id
+               ORDER BY MAX(m.score) DESC, p.canonical_id
+               LIMIT $3 OFFSET $4"#,
+            Entity::from(kind).as_str(),
+            search,
+            limit,
+            offset,
+            like_pattern(search),
+            normalized,
+            like_pattern(&normalized),
+            folded,
+            like_pattern(&folded),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Search media: {e}")))?;
+
+        Ok(RankedIds {
+            total: rows
+                .first()
+                .map_or(0, |row| usize::try_from(row.total).unwrap_or_default()),
+            ids: rows.into_iter().map(|row| row.canonical_id).collect(),
+        })
+    }
+
+    pub async fn search_character_ids(
+        &self,
+        provider: Provider,
+        search: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<RankedIds<i32>, AppError> {
+        let (normalized, folded) = unspaced_forms(search);
+        let rows = query!(
+            r#"SELECT c.id, COUNT(*) OVER () AS "total!"
+               FROM catalog_characters c
+               WHERE c.provider = $1 AND (
+                   $2 = '' OR c.name % $2 OR c.name ILIKE $5 OR c.name_native ILIKE $5
+                   OR ($6 <> '' AND (
+                       c.normalized % $6 OR c.normalized ILIKE $7
+                       OR c.normalized % $8 OR c.normalized ILIKE $9
+                   ))
+                   OR EXISTS (
+                       SELECT 1 FROM catalog_character_nicknames n
+                       WHERE n.provider = c.provider AND n.character_id = c.id
+                           AND (n.nickname % $2 OR n.nickname ILIKE $5)
+                   )
+               )
+               ORDER BY GREATEST(
+                       similarity(c.name, $2),
+                       similarity(c.normalized, $6) * 0.9::real,
+                       similarity(c.normalized, $8) * 0.9::real
+                   ) DESC,
+                   c.favorites DESC NULLS LAST, c.id
+               LIMIT $3 OFFSET $4"#,
+            provider.as_str(),
+            search,
+            limit,
+            offset,
+            like_pattern(search),
+            normalized,
+            like_pattern(&normalized),
+            folded,
+            like_pattern(&folded),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Search characters: {e}")))?;
+
+        Ok(RankedIds {
+            total: rows
+                .first()
+                .map_or(0, |row| usize::try_from(row.total).unwrap_or_default()),
+            ids: rows.into_iter().map(|row| row.id).collect(),
+        })
+    }
+
+    pub async fn vocabulary_terms(&self) -> Result<Vec<String>, AppError> {
+        query_scalar!(
+            r#"SELECT title AS "term!" FROM catalog_media_titles
+               UNION SELECT name FROM catalog_media_terms WHERE category = 'synonym'
+               UNION SELECT name FROM catalog_characters"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Load search vocabulary: {e}")))
+    }
+}
@@ -14,6 +14,10 @@ pub enum AppError {
     DatabaseError(String),
     #[error("Invalid data provided: {0}")]
     InvalidData(String),
+    #[error("Provider error: {0}")]
+    ProviderError(String),
+    #[error("Search error: {0}")]
+    SearchError(String),
 }
 
 impl AppError {
@@ -22,8 +26,11 @@ impl AppError {
         match self {
             Self::NotFound => StatusCode::NOT_FOUND,
             Self::InvalidSessionId(_) => StatusCode::UNAUTHORIZED,
-            Self::InternalError(_) | Self::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InternalError(_) | Self::DatabaseError(_) | Self::SearchError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
             Self::InvalidData(_) => StatusCode::NOT_ACCEPTABLE,
+            Self::ProviderError(_) => StatusCode::BAD_GATEWAY,
         }
     }
 }
@@ -12,14 +12,18 @@ async fn main() {
     use opentelemetry_otlp::{SpanExporter, WithExportConfig as _};
     use opentelemetry_sdk::{Resource, runtime::Tokio, trace::TracerProvider};
     use otakuhub::{
-        apis::tracker::*,
+        apis::{indexer::configure_indexes, tracker::*},
         app::{App, shell},
         config::{
-            settings::{APISConfig, AuthConfig, MainConfig, MeilisearchConfig, PostgresConfig},
+            settings::{
+                APISConfig, AuthConfig, MainConfig, MeilisearchConfig, PostgresConfig,
+                SearchBackendKind, SearchConfig,
+            },
             types::{AppState, MEILISEARCH_CLIENT, UTILS_CONFIG, UtilsConfig},
         },
         db::backend::PostgreSQLBackend,
         routes::{leptos_routes_handler, server_func_handler},
+        utils::analytics::purge_search_events,
     };
     use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
     use std::{fs::read_to_string, net::SocketAddr, sync::Arc};
@@ -36,7 +40,7 @@ async fn main() {
     };
     use tower_sessions::{Expiry, SessionManagerLayer, session_store::ExpiredDeletion};
     use tower_sessions_sqlx_store::PostgresStore;
-    use tracing::{Level, info};
+    use tracing::{Level, info, warn};
     use tracing_opentelemetry::layer;
     use tracing_subscriber::{
         Registry, filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt as _,
@@ -50,9 +54,15 @@ async fn main() {
     let postgres_config: PostgresConfig =
         Value::try_into(config_toml["PostgreSQL"].clone()).unwrap();
     let auth_config: AuthConfig = Value::try_into(config_toml["Auth"].clone()).unwrap();
-    let meilisearch_config: MeilisearchConfig =
-        Value::try_into(config_toml["Meilisearch"].clone()).unwrap();
+    let meilisearch_config: Option<MeilisearchConfig> = config_toml
+        .get("Meilisearch")
+        .map(|meilisearch| Value::try_into(meilisearch.clone()).unwrap());
     let apis_config: APISConfig = Value::try_into(config_toml["APIS"].clone()).unwrap();
+    let search_config: SearchConfig = config_toml
+        .get("Search")
+        .map_or_else(SearchConfig::default, |search| {
+            Value::try_into(search.clone()).unwrap()
+        });
 
     let pool_options = PgConnectOptions::new()
         .host(&postgres_config.host)
@@ -83,17 +93,28 @@ async fn main() {
             )),
     );
 
+    let analytics_retention_days = search_config.analytics_retention_days;
+    let search_backend = search_config.backend;
     UTILS_CONFIG
         .set(Arc::new(UtilsConfig {
             main: main_config.clone(),
+            admins: auth_config.admins,
+            apis: apis_config.clone(),
+            search: search_config,
         }))
         .unwrap();
 
-    MEILISEARCH_CLIENT
-        .set(Arc::new(
-            MClient::new(meilisearch_config.host, Some(meilisearch_config.master_key)).unwrap(),
-        ))
-        .unwrap();
+    // Without a client nothing is indexed, so Postgres-only deployments never reach for
+    // Meilisearch
+    if search_backend == SearchBackendKind::Meilisearch
+        && let Some(meilisearch_config) = meilisearch_config
+    {
+        MEILISEARCH_CLIENT
+            .set(Arc::new(
+                MClient::new(meilisearch_config.host, Some(meilisearch_config.master_key)).unwrap(),
+            ))
+            .unwrap();
+    }
 
     let log_level = match main_config.log_level.as_str() {
         "trace" => Level::TRACE,
@@ -125,14 +146,26 @@ async fn main() {
         .with(layer().with_tracer(provider.tracer(main_config.site_name)))
         .init();
 
+    if MEILISEARCH_CLIENT.get().is_none() {
+        info!("Meilisearch not configured, searching and syncing with Postgres only");
+    } else if let Err(e) = configure_indexes().await {
+        warn!("Meilisearch index settings not applied: {e}");
+    }
+
     let backend = PostgreSQLBackend::new(pool.clone());
-    let auth_session_layer =
-        ServiceBuilder::new().layer(AuthManagerLayerBuilder::new(backend, session_layer).build());
+    backend.migrate().await.unwrap();
+    spawn(purge_search_events(
+        backend.clone(),
+        analytics_retention_days,
+    ));
 
-    start_api_scraping_tasks(apis_config.fetch_interval_hours)
+    start_api_scraping_tasks(backend.clone(), &apis_config)
         .await
         .unwrap();
 
+    let auth_session_layer =
+        ServiceBuilder::new().layer(AuthManagerLayerBuilder::new(backend, session_layer).build());
+
     let conf = get_configuration(None).expect("config set in Cargo.toml");
 
     let mut leptos_options = conf.leptos_options;
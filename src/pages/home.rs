@@ -1,4 +1,7 @@
-use crate::components::{footer::Footer, nav_bar::NavBar};
+use crate::{
+    components::{footer::Footer, nav_bar::NavBar},
+    utils::search::trending_searches,
+};
 use leptos::prelude::*;
 use leptos_meta::Title;
 
@@ -17,9 +20,44 @@ pub fn HomePage() -> impl IntoView {
                             Connect with fellow otaku and dive deep into the world of Japanese entertainment.
                         </p>
                     </div>
+                    <TrendingSearches />
                 </div>
                 <Footer />
             </div>
         </main>
     }
 }
+
+#[component]
+fn TrendingSearches() -> impl IntoView {
+    let trending = Resource::new(|| (), |()| trending_searches());
+    view! {
+        <Suspense>
+            {move || Suspend::new(async move {
+                let searches = trending.await.unwrap_or_default();
+                (!searches.is_empty())
+                    .then(|| {
+                        view! {
+                            <section class="mx-auto max-w-4xl text-center">
+                                <h2 class="mb-4 text-2xl font-bold text-primary">
+                                    "Trending searches"
+                                </h2>
+                                <div class="flex flex-wrap gap-2 justify-center">
+                                    {searches
+                                        .into_iter()
+                                        .map(|search| {
+                                            view! {
+                                                <a href=search.href() class="badge badge-outline">
+                                                    {search.query}
+                                                </a>
+                                            }
+                                        })
+                                        .collect::<Vec<_>>()}
+                                </div>
+                            </section>
+                        }
+                    })
+            })}
+        </Suspense>
+    }
+}
@@ -1,19 +1,55 @@
 use crate::{
+    apis::model::{
+        CanonicalMedia, Character, Highlight, MEDIA_FACETS, Person, SearchCategory, SearchFilters,
+        SearchGroup, SearchResult,
+    },
     components::{footer::Footer, nav_bar::NavBar},
-    utils::search::search_engine,
+    utils::search::{record_search_click, search_engine},
 };
 use leptos::prelude::*;
 use leptos_meta::Title;
-use leptos_router::hooks::use_query_map;
+use leptos_router::hooks::{use_params_map, use_query_map};
+use std::collections::BTreeMap;
+use urlencoding::encode;
+
+const MIN_SCORES: [f32; 5] = [5.0, 6.0, 7.0, 8.0, 9.0];
+const PAGINATION_WINDOW: i32 = 2;
 
 #[component]
 pub fn SearchPage() -> impl IntoView {
+    let params = use_params_map();
     let query = use_query_map();
-    let search_term = move || query.read().get("q").unwrap();
+    let category = move || {
+        params
+            .read()
+            .get("category")
+            .and_then(|category| SearchCategory::from_name(&category))
+            .unwrap_or_default()
+    };
+    let search_term = move || query.read().get("q").unwrap_or_default();
+    let filters = move || SearchFilters::from_query(|key| query.read().get(key));
+    let page = move || {
+        query
+            .read()
+            .get("page")
+            .and_then(|page| page.parse::<i32>().ok())
+            .unwrap_or(1)
+            .max(1)
+    };
     let search_results = Resource::new(
-        move || search_term(),
-        |term| async move { search_engine(term).await.unwrap() },
+        move || (category(), search_term(), filters(), page()),
+        |(category, term, filters, page)| async move {
+            search_engine(category, term, filters, page).await
+        },
     );
+    let filter_href = move |filters: SearchFilters| {
+        format!(
+            "/search/{}?q={}{}",
+            category().as_str(),
+            encode(&search_term()),
+            filters.to_query()
+        )
+    };
     view! {
         <Title text="Otakuhub: Next-generation animanga platform!" />
         <main>
@@ -25,7 +61,7 @@ pub fn SearchPage() -> impl IntoView {
                         {move || {
                             view! {
                                 <p class="text-xl text-primary">
-                                    "Showing results for: " {search_term()}
+                                    "Showing " {category().as_str()} " results for: " {search_term()}
                                 </p>
                             }
                         }}
@@ -53,35 +89,104 @@ pub fn SearchPage() -> impl IntoView {
                         {move || {
                             search_results
                                 .get()
-                                .map(|results| {
+                                .map(|response| {
+                                    let response = match response {
+                                        Ok(response) => response,
+                                        Err(e) => {
+                                            let message = match e {
+                                                ServerFnError::ServerError(message) => message,
+                                                e => e.to_string(),
+                                            };
+                                            return view! {
+                                                <p class="text-center text-error">{message}</p>
+                                            }
+                                                .into_any();
+                                        }
+                                    };
+                                    let sidebar = (!response.facets.is_empty())
+                                        .then(|| {
+                                            view! {
+                                                <FilterSidebar
+                                                    facets=response.facets
+                                                    filters=filters()
+                                                    href=filter_href
+                                                />
+                                            }
+                                        });
+                                    let summary = response
+                                        .total_hits
+                                        .map(|total| {
+                                            view! {
+                                                <p class="mb-4 text-sm text-center opacity-70">
+                                                    {format!("About {total} results")}
+                                                </p>
+                                            }
+                                        });
+                                    let pagination = view! {
+                                        <Pagination
+                                            page=response.page
+                                            total_pages=response.total_pages
+                                            has_next_page=response.has_next_page
+                                            href=move |page| format!("{}&page={page}", filter_href(filters()))
+                                        />
+                                    };
+                                    let corrections = (!response.corrections.is_empty())
+                                        .then(|| {
+                                            view! {
+                                                <DidYouMean
+                                                    corrections=response.corrections.clone()
+                                                    href=move |correction: &str| {
+                                                        format!(
+                                                            "/search/{}?q={}{}",
+                                                            category().as_str(),
+                                                            encode(correction),
+                                                            filters().to_query(),
+                                                        )
+                                                    }
+                                                />
+                                            }
+                                        });
+                                    let empty = (response.result_count() == 0)
+                                        .then(|| {
+                                            view! {
+                                                <p class="mb-4 text-center opacity-70">
+                                                    "No results found."
+                                                </p>
+                                            }
+                                        });
+                                    let event_id = response.event_id;
+                                    let results = if response.groups.is_empty() {
+                                        view! { <ResultGrid results=response.results event_id /> }
+                                            .into_any()
+                                    } else {
+                                        response
+                                            .groups
+                                            .into_iter()
+                                            .map(move |group| {
+                                                let href = format!(
+                                                    "/search/{}?q={}{}",
+                                                    group.category.as_str(),
+                                                    encode(&search_term()),
+                                                    filters().to_query(),
+                                                );
+                                                view! { <ResultSection group href event_id /> }
+                                            })
+                                            .collect::<Vec<_>>()
+                                            .into_any()
+                                    };
                                     view! {
-                                        <div class="flex justify-center">
-                                            <div class="grid grid-cols-2 gap-4 w-full sm:grid-cols-3 md:grid-cols-4 lg:grid-cols-5 max-w-[1200px]">
-                                                {results
-                                                    .into_iter()
-                                                    .map(|result| {
-                                                        view! {
-                                                            <div class="flex flex-col justify-center items-center">
-                                                                <div class="flex flex-col items-center w-full max-w-xs">
-                                                                    <img
-                                                                        src=result.images.webp.image_url.clone()
-                                                                        alt=format!(
-                                                                            "Anime: {}",
-                                                                            result.titles.first().unwrap().title,
-                                                                        )
-                                                                        class="object-cover mb-2 w-full rounded-lg h-[300px]"
-                                                                    />
-                                                                    <p class="text-sm text-center line-clamp-2">
-                                                                        {result.titles.first().unwrap().title.clone()}
-                                                                    </p>
-                                                                </div>
-                                                            </div>
-                                                        }
-                                                    })
-                                                    .collect::<Vec<_>>()}
+                                        {summary}
+                                        {empty}
+                                        {corrections}
+                                        <div class="flex flex-col gap-6 justify-center md:flex-row">
+                                            {sidebar}
+                                            <div class="flex flex-col gap-8 w-full max-w-[1200px]">
+                                                {results}
                                             </div>
                                         </div>
+                                        {pagination}
                                     }
+                                        .into_any()
                                 })
                         }}
                     </Suspense>
@@ -91,3 +196,368 @@ pub fn SearchPage() -> impl IntoView {
         </main>
     }
 }
+
+#[component]
+fn DidYouMean(corrections: Vec<String>, href: impl Fn(&str) -> String + 'static) -> impl IntoView {
+    let links = corrections
+        .into_iter()
+        .enumerate()
+        .map(|(index, correction)| {
+            view! {
+                {(index > 0).then_some(", ")}
+                <a href=href(&correction) class="italic font-bold link link-primary">
+                    {correction.clone()}
+                </a>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! { <p class="mb-4 text-center">"Did you mean: " {links} "?"</p> }
+}
+
+#[component]
+fn Pagination(
+    page: i32,
+    total_pages: Option<usize>,
+    has_next_page: bool,
+    href: impl Fn(i32) -> String + 'static,
+) -> impl IntoView {
+    let last_page = total_pages
+        .and_then(|total| i32::try_from(total).ok())
+        .unwrap_or(if has_next_page { page + 1 } else { page });
+    let window = (page - PAGINATION_WINDOW).max(1)..=(page + PAGINATION_WINDOW).min(last_page);
+    let previous = (page > 1).then(|| {
+        view! {
+            <a href=href(page - 1) class="join-item btn btn-sm">
+                "«"
+            </a>
+        }
+    });
+    let next = (page < last_page).then(|| {
+        view! {
+            <a href=href(page + 1) class="join-item btn btn-sm">
+                "»"
+            </a>
+        }
+    });
+    let pages = window
+        .map(|number| {
+            view! {
+                <a href=href(number) class="join-item btn btn-sm" class:btn-active=number == page>
+                    {number}
+                </a>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    (last_page > 1).then(|| {
+        view! {
+            <nav class="flex justify-center mt-8">
+                <div class="join">{previous} {pages} {next}</div>
+            </nav>
+        }
+    })
+}
+
+#[component]
+fn FilterSidebar(
+    facets: BTreeMap<String, BTreeMap<String, usize>>,
+    filters: SearchFilters,
+    href: impl Fn(SearchFilters) -> String + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let clear = (!filters.is_empty()).then(|| {
+        view! {
+            <a href=href(SearchFilters::default()) class="text-xs link">
+                "Clear filters"
+            </a>
+        }
+    });
+    let min_scores = MIN_SCORES
+        .into_iter()
+        .map(|score| {
+            let active = filters.min_score == Some(score);
+            let toggled = SearchFilters {
+                min_score: (!active).then_some(score),
+                ..filters.clone()
+            };
+            view! {
+                <a href=href(toggled) class="badge badge-outline" class:badge-primary=active>
+                    {format!("{score}+")}
+                </a>
+            }
+        })
+        .collect::<Vec<_>>();
+    let sections = MEDIA_FACETS
+        .into_iter()
+        .filter_map(|facet| {
+            let mut values = facets.get(facet)?.iter().collect::<Vec<_>>();
+            values.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+            let options = values
+                .into_iter()
+                .map(|(value, count)| {
+                    view! {
+                        <FacetOption
+                            facet
+                            value=value.clone()
+                            count=*count
+                            filters=filters.clone()
+                            href
+                        />
+                    }
+                })
+                .collect::<Vec<_>>();
+            Some(view! {
+                <div class="mb-4">
+                    <h3 class="mb-1 text-sm font-bold capitalize">{facet}</h3>
+                    <ul class="overflow-y-auto max-h-48 text-sm">{options}</ul>
+                </div>
+            })
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <aside class="w-full md:w-56 shrink-0">
+            <div class="flex justify-between items-center mb-4">
+                <h2 class="text-lg font-bold text-primary">Filters</h2>
+                {clear}
+            </div>
+            <div class="mb-4">
+                <h3 class="mb-1 text-sm font-bold">Minimum score</h3>
+                <div class="flex flex-wrap gap-1">{min_scores}</div>
+            </div>
+            {sections}
+        </aside>
+    }
+}
+
+#[component]
+fn FacetOption(
+    facet: &'static str,
+    value: String,
+    count: usize,
+    filters: SearchFilters,
+    href: impl Fn(SearchFilters) -> String + 'static,
+) -> impl IntoView {
+    let toggle = |selected: &mut Option<String>| {
+        *selected = if selected.as_ref() == Some(&value) {
+            None
+        } else {
+            Some(value.clone())
+        };
+    };
+    let toggle_in = |list: &mut Vec<String>| {
+        if let Some(position) = list.iter().position(|item| *item == value) {
+            list.remove(position);
+        } else {
+            list.push(value.clone());
+        }
+    };
+
+    let mut included = filters.clone();
+    let mut excluded = None;
+    let active = match facet {
+        "genres" => {
+            toggle_in(&mut included.genres);
+            included.exclude_genres.retain(|genre| *genre != value);
+            let mut exclude = filters.clone();
+            toggle_in(&mut exclude.exclude_genres);
+            exclude.genres.retain(|genre| *genre != value);
+            excluded = Some((href(exclude), filters.exclude_genres.contains(&value)));
+            filters.genres.contains(&value)
+        }
+        "year" => {
+            let year = value.parse().ok();
+            let active = filters.year_from == year && filters.year_to == year;
+            (included.year_from, included.year_to) =
+                if active { (None, None) } else { (year, year) };
+            active
+        }
+        "season" => {
+            toggle(&mut included.season);
+            filters.season.as_ref() == Some(&value)
+        }
+        "status" => {
+            toggle(&mut included.status);
+            filters.status.as_ref() == Some(&value)
+        }
+        "type" => {
+            toggle(&mut included.media_type);
+            filters.media_type.as_ref() == Some(&value)
+        }
+        _ => {
+            toggle(&mut included.rating);
+            filters.rating.as_ref() == Some(&value)
+        }
+    };
+    let exclude = excluded.map(|(exclude_href, excluded)| {
+        view! {
+            <a href=exclude_href class="ml-1 opacity-60" class:text-error=excluded title="Exclude">
+                "−"
+            </a>
+        }
+    });
+
+    view! {
+        <li class="flex justify-between items-center py-0.5">
+            <a href=href(included) class="truncate hover:underline" class:font-bold=active>
+                {value.clone()}
+            </a>
+            <span class="flex items-center text-xs opacity-70">{count} {exclude}</span>
+        </li>
+    }
+}
+
+#[component]
+fn ResultGrid(results: Vec<SearchResult>, event_id: Option<i64>) -> impl IntoView {
+    let record_click = Action::new(move |result: &String| {
+        let result = result.clone();
+        async move {
+            if let Some(event_id) = event_id {
+                record_search_click(event_id, result).await.ok();
+            }
+        }
+    });
+    view! {
+        <div class="grid grid-cols-2 gap-4 w-full sm:grid-cols-3 md:grid-cols-4 lg:grid-cols-5">
+            {results
+                .into_iter()
+                .map(|result| {
+                    let key = result.key();
+                    let card = match result {
+                        SearchResult::Anime(anime) => view! { <AnimeCard anime /> }.into_any(),
+                        SearchResult::Manga(manga) => view! { <MangaCard manga /> }.into_any(),
+                        SearchResult::Character(character) => {
+                            view! { <CharacterCard character /> }.into_any()
+                        }
+                        SearchResult::People(person) => view! { <PersonCard person /> }.into_any(),
+                    };
+                    view! {
+                        <div on:click=move |_| {
+                            record_click.dispatch(key.clone());
+                        }>{card}</div>
+                    }
+                })
+                .collect::<Vec<_>>()}
+        </div>
+    }
+}
+
+#[component]
+fn ResultSection(group: SearchGroup, href: String, event_id: Option<i64>) -> impl IntoView {
+    let heading = match group.total {
+        Some(total) => format!("{} ({total})", group.category.as_str()),
+        None => group.category.as_str().to_string(),
+    };
+    view! {
+        <section>
+            <div class="flex justify-between items-center mb-4">
+                <h2 class="text-2xl font-bold capitalize text-primary">{heading}</h2>
+                <a href=href class="text-sm link">
+                    "See all"
+                </a>
+            </div>
+            <ResultGrid results=group.results event_id />
+        </section>
+    }
+}
+
+#[component]
+fn ResultCard(image_url: Option<String>, alt: String, children: Children) -> impl IntoView {
+    view! {
+        <div class="flex flex-col justify-center items-center">
+            <div class="flex flex-col items-center w-full max-w-xs">
+                <img src=image_url alt=alt class="object-cover mb-2 w-full rounded-lg h-[300px]" />
+                {children()}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn AnimeCard(anime: CanonicalMedia) -> impl IntoView {
+    let details = [
+        anime.media_type.clone(),
+        anime.episodes.map(|episodes| format!("{episodes} eps")),
+        anime.year.map(|year| year.to_string()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+    view! {
+        <ResultCard image_url=anime.image_url.clone() alt=format!("Anime: {}", anime.title())>
+            <p class="text-sm text-center line-clamp-2">{anime.title().to_owned()}</p>
+            <p class="text-xs text-center opacity-70">{details}</p>
+            <Highlights highlights=anime.highlights />
+        </ResultCard>
+    }
+}
+
+#[component]
+fn MangaCard(manga: CanonicalMedia) -> impl IntoView {
+    let details = [
+        manga.media_type.clone(),
+        manga.chapters.map(|chapters| format!("{chapters} ch")),
+        manga.volumes.map(|volumes| format!("{volumes} vol")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+    view! {
+        <ResultCard image_url=manga.image_url.clone() alt=format!("Manga: {}", manga.title())>
+            <p class="text-sm text-center line-clamp-2">{manga.title().to_owned()}</p>
+            <p class="text-xs text-center opacity-70">{details}</p>
+            <Highlights highlights=manga.highlights />
+        </ResultCard>
+    }
+}
+
+#[component]
+fn CharacterCard(character: Character) -> impl IntoView {
+    view! {
+        <ResultCard image_url=character.image_url alt=format!("Character: {}", character.name)>
+            <p class="text-sm text-center line-clamp-2">{character.name}</p>
+            <p class="text-xs text-center opacity-70">{character.name_native}</p>
+            <Highlights highlights=character.highlights />
+        </ResultCard>
+    }
+}
+
+#[component]
+fn PersonCard(person: Person) -> impl IntoView {
+    view! {
+        <ResultCard image_url=person.image_url alt=format!("Person: {}", person.name)>
+            <p class="text-sm text-center line-clamp-2">{person.name}</p>
+            <p class="text-xs text-center opacity-70">{person.name_native}</p>
+            <p class="text-xs text-center opacity-70">{person.occupations.join(", ")}</p>
+        </ResultCard>
+    }
+}
+
+#[component]
+fn Highlights(highlights: Vec<Highlight>) -> impl IntoView {
+    highlights
+        .into_iter()
+        .map(|highlight| {
+            let fragments = highlight
+                .fragments
+                .into_iter()
+                .map(|fragment| {
+                    if fragment.matched {
+                        view! { <mark class="bg-transparent font-bold text-primary">{fragment.text}</mark> }
+                            .into_any()
+                    } else {
+                        fragment.text.into_any()
+                    }
+                })
+                .collect::<Vec<_>>();
+            view! {
+                <p class="mt-1 text-xs text-center opacity-70 line-clamp-3">
+                    <span class="font-bold">{highlight.label()} ": "</span>
+                    {fragments}
+                </p>
+            }
+        })
+        .collect::<Vec<_>>()
+}
@@ -13,6 +13,7 @@ use tower_sessions::Session;
 
 pub async fn leptos_routes_handler(
     auth_session: AuthSession<PostgreSQLBackend>,
+    session: Session,
     state: State<AppState>,
     req: Request<Body>,
 ) -> Response {
@@ -21,6 +22,7 @@ pub async fn leptos_routes_handler(
         app_state.routes.clone(),
         move || {
             provide_context(auth_session.clone());
+            provide_context(session.clone());
             provide_context(app_state.pool.clone());
         },
         move || shell(app_state.leptos_options.clone()),
@@ -0,0 +1,17 @@
+use crate::{config::consts::SEARCH_EVENT_PURGE_INTERVAL_SECONDS, db::backend::PostgreSQLBackend};
+use tokio::time::{Duration, interval};
+use tracing::{error, info};
+
+pub async fn purge_search_events(backend: PostgreSQLBackend, retention_days: i32) {
+    let mut ticks = interval(Duration::from_secs(SEARCH_EVENT_PURGE_INTERVAL_SECONDS));
+    loop {
+        ticks.tick().await;
+        match backend.purge_search_events(retention_days).await {
+            Ok(0) => {}
+            Ok(purged) => {
+                info!("Purged {purged} search events older than {retention_days} days");
+            }
+            Err(e) => error!("Purging search events failed: {e}"),
+        }
+    }
+}
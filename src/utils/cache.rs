@@ -0,0 +1,263 @@
+use crate::{
+    apis::model::{
+        CacheStats, Character, Media, MediaKind, Provider, SearchCategory, SearchFilters,
+        SearchResponse,
+    },
+    config::consts::{
+        CATALOG_CACHE_CAPACITY, CATALOG_CACHE_TTL_SECONDS, PROVIDER_CACHE_CAPACITY,
+        PROVIDER_CACHE_TTL_SECONDS, SEARCH_CACHE_CAPACITY, SEARCH_CACHE_TTL_SECONDS,
+        VOCABULARY_TTL_SECONDS,
+    },
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+    utils::spelling::Vocabulary,
+};
+use mini_moka::sync::Cache;
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+use tracing::info;
+
+pub struct CountingCache<K, V> {
+    name: &'static str,
+    cache: Cache<K, V>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K, V> CountingCache<K, V>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    fn new(name: &'static str, capacity: u64, ttl_seconds: u64) -> Self {
+        Self {
+            name,
+            cache: Cache::builder()
+                .max_capacity(capacity)
+                .time_to_live(Duration::from_secs(ttl_seconds))
+                .build(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let value = self.cache.get(key);
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    /// Only successful lookups are stored, errors are always retried
+    pub async fn get_or_try_insert<F>(&self, key: K, fetch: F) -> Result<V, AppError>
+    where
+        F: Future<Output = Result<V, AppError>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+        let value = fetch.await?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+
+    pub fn invalidate_all(&self) {
+        self.cache.invalidate_all();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name.to_string(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.cache.entry_count(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SearchKey {
+    pub category: SearchCategory,
+    pub query: String,
+    pub filters: String,
+    pub page: i32,
+}
+
+impl SearchKey {
+    /// Queries differing only in case or spacing share an entry
+    #[must_use]
+    pub fn new(category: SearchCategory, query: &str, filters: &SearchFilters, page: i32) -> Self {
+        Self {
+            category,
+            query: query
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase(),
+            filters: filters.to_query(),
+            page,
+        }
+    }
+}
+
+pub static SEARCH_CACHE: Lazy<CountingCache<SearchKey, SearchResponse>> =
+    Lazy::new(|| CountingCache::new("search", SEARCH_CACHE_CAPACITY, SEARCH_CACHE_TTL_SECONDS));
+pub static MEDIA_CACHE: Lazy<CountingCache<(Provider, MediaKind, i32), Option<Media>>> =
+    Lazy::new(|| CountingCache::new("media", CATALOG_CACHE_CAPACITY, CATALOG_CACHE_TTL_SECONDS));
+pub static CHARACTER_CACHE: Lazy<CountingCache<(Provider, i32), Option<Character>>> =
+    Lazy::new(|| {
+        CountingCache::new(
+            "characters",
+            CATALOG_CACHE_CAPACITY,
+            CATALOG_CACHE_TTL_SECONDS,
+        )
+    });
+// Provider API responses, kept apart from the catalog records they may be newer than
+pub static PROVIDER_MEDIA_CACHE: Lazy<CountingCache<(Provider, MediaKind, i32), Option<Media>>> =
+    Lazy::new(|| {
+        CountingCache::new(
+            "provider_media",
+            PROVIDER_CACHE_CAPACITY,
+            PROVIDER_CACHE_TTL_SECONDS,
+        )
+    });
+pub static PROVIDER_CHARACTER_CACHE: Lazy<CountingCache<(Provider, i32), Option<Character>>> =
+    Lazy::new(|| {
+        CountingCache::new(
+            "provider_characters",
+            PROVIDER_CACHE_CAPACITY,
+            PROVIDER_CACHE_TTL_SECONDS,
+        )
+    });
+
+// The whole vocabulary is one entry, rebuilt from the catalog once invalidated or expired
+pub static VOCABULARY_CACHE: Lazy<CountingCache<(), Arc<Vocabulary>>> =
+    Lazy::new(|| CountingCache::new("vocabulary", 1, VOCABULARY_TTL_SECONDS));
+
+pub async fn media_by_id(
+    provider: Provider,
+    kind: MediaKind,
+    id: i32,
+) -> Result<Option<Media>, AppError> {
+    PROVIDER_MEDIA_CACHE
+        .get_or_try_insert(
+            (provider, kind, id),
+            provider.metadata().media_by_id(kind, id),
+        )
+        .await
+}
+
+pub async fn character_by_id(provider: Provider, id: i32) -> Result<Option<Character>, AppError> {
+    PROVIDER_CHARACTER_CACHE
+        .get_or_try_insert((provider, id), provider.metadata().character_by_id(id))
+        .await
+}
+
+/// Catalog records for the Postgres search backend, loading only the ids not cached yet
+pub async fn catalog_media(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    kind: MediaKind,
+    ids: &[i32],
+) -> Result<Vec<Media>, AppError> {
+    cached_records(
+        &MEDIA_CACHE,
+        ids,
+        |id| (provider, kind, id),
+        |missing| async move { backend.load_media(provider, kind, &missing).await },
+        |media| media.id,
+    )
+    .await
+}
+
+pub async fn catalog_characters(
+    backend: &PostgreSQLBackend,
+    provider: Provider,
+    ids: &[i32],
+) -> Result<Vec<Character>, AppError> {
+    cached_records(
+        &CHARACTER_CACHE,
+        ids,
+        |id| (provider, id),
+        |missing| async move { backend.load_characters(provider, &missing).await },
+        |character| character.id,
+    )
+    .await
+}
+
+// Ids the catalog doesn't have are cached as missing too, so they aren't queried again
+async fn cached_records<K, T, F>(
+    cache: &CountingCache<K, Option<T>>,
+    ids: &[i32],
+    key: impl Fn(i32) -> K,
+    load: impl FnOnce(Vec<i32>) -> F,
+    record_id: impl Fn(&T) -> i32,
+) -> Result<Vec<T>, AppError>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
+    F: Future<Output = Result<Vec<T>, AppError>>,
+{
+    let mut records = Vec::new();
+    let mut missing = Vec::new();
+    for &id in ids {
+        match cache.get(&key(id)) {
+            Some(record) => records.extend(record),
+            None => missing.push(id),
+        }
+    }
+    if missing.is_empty() {
+        return Ok(records);
+    }
+
+    let mut loaded: HashMap<i32, T> = load(missing.clone())
+        .await?
+        .into_iter()
+        .map(|record| (record_id(&record), record))
+        .collect();
+    for id in missing {
+        let record = loaded.remove(&id);
+        cache.insert(key(id), record.clone());
+        records.extend(record);
+    }
+
+    Ok(records)
+}
+
+pub fn invalidate_caches() {
+    SEARCH_CACHE.invalidate_all();
+    MEDIA_CACHE.invalidate_all();
+    CHARACTER_CACHE.invalidate_all();
+    PROVIDER_MEDIA_CACHE.invalidate_all();
+    PROVIDER_CHARACTER_CACHE.invalidate_all();
+    VOCABULARY_CACHE.invalidate_all();
+    info!("Invalidated search, catalog, provider and vocabulary caches");
+}
+
+#[must_use]
+pub fn cache_stats() -> Vec<CacheStats> {
+    vec![
+        SEARCH_CACHE.stats(),
+        MEDIA_CACHE.stats(),
+        CHARACTER_CACHE.stats(),
+        PROVIDER_MEDIA_CACHE.stats(),
+        PROVIDER_CHARACTER_CACHE.stats(),
+        VOCABULARY_CACHE.stats(),
+    ]
+}
@@ -1 +1,15 @@
+use cfg_if::cfg_if;
+
 pub mod search;
+
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        pub mod analytics;
+        pub mod cache;
+        pub mod hash;
+        pub mod normalize;
+        pub mod query;
+        pub mod search_backend;
+        pub mod spelling;
+    }
+}
@@ -0,0 +1,303 @@
+const HALF_WIDTH_KANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン゛゜";
+
+const HIRAGANA_ROMAJI: [(char, &str); 86] = [
+    ('ぁ', "a"),
+    ('あ', "a"),
+    ('ぃ', "i"),
+    ('い', "i"),
+    ('ぅ', "u"),
+    ('う', "u"),
+    ('ぇ', "e"),
+    ('え', "e"),
+    ('ぉ', "o"),
+    ('お', "o"),
+    ('か', "ka"),
+    ('が', "ga"),
+    ('き', "ki"),
+    ('ぎ', "gi"),
+    ('く', "ku"),
+    ('ぐ', "gu"),
+    ('け', "ke"),
+    ('げ', "ge"),
+    ('こ', "ko"),
+    ('ご', "go"),
+    ('さ', "sa"),
+    ('ざ', "za"),
+    ('し', "shi"),
+    ('じ', "ji"),
+    ('す', "su"),
+    ('ず', "zu"),
+    ('せ', "se"),
+    ('ぜ', "ze"),
+    ('そ', "so"),
+    ('ぞ', "zo"),
+    ('た', "ta"),
+    ('だ', "da"),
+    ('ち', "chi"),
+    ('ぢ', "ji"),
+    ('っ', ""),
+    ('つ', "tsu"),
+    ('づ', "zu"),
+    ('て', "te"),
+    ('で', "de"),
+    ('と', "to"),
+    ('ど', "do"),
+    ('な', "na"),
+    ('に', "ni"),
+    ('ぬ', "nu"),
+    ('ね', "ne"),
+    ('の', "no"),
+    ('は', "ha"),
+    ('ば', "ba"),
+    ('ぱ', "pa"),
+    ('ひ', "hi"),
+    ('び', "bi"),
+    ('ぴ', "pi"),
+    ('ふ', "fu"),
+    ('ぶ', "bu"),
+    ('ぷ', "pu"),
+    ('へ', "he"),
+    ('べ', "be"),
+    ('ぺ', "pe"),
+    ('ほ', "ho"),
+    ('ぼ', "bo"),
+    ('ぽ', "po"),
+    ('ま', "ma"),
+    ('み', "mi"),
+    ('む', "mu"),
+    ('め', "me"),
+    ('も', "mo"),
+    ('ゃ', "ya"),
+    ('や', "ya"),
+    ('ゅ', "yu"),
+    ('ゆ', "yu"),
+    ('ょ', "yo"),
+    ('よ', "yo"),
+    ('ら', "ra"),
+    ('り', "ri"),
+    ('る', "ru"),
+    ('れ', "re"),
+    ('ろ', "ro"),
+    ('ゎ', "wa"),
+    ('わ', "wa"),
+    ('ゐ', "i"),
+    ('ゑ', "e"),
+    ('を', "o"),
+    ('ん', "n"),
+    ('ゔ', "vu"),
+    ('ゕ', "ka"),
+    ('ゖ', "ke"),
+];
+
+const LONG_VOWELS: [(&str, &str); 10] = [
+    ("ā", "a"),
+    ("â", "a"),
+    ("ī", "i"),
+    ("î", "i"),
+    ("ū", "u"),
+    ("û", "u"),
+    ("ē", "e"),
+    ("ê", "e"),
+    ("ō", "o"),
+    ("ô", "o"),
+];
+
+// Romaji spelled from kana always folds these, since とう and とお both mean a long o. Latin
+// text only folds them in the extra form from `search_forms`, as "moon" is a real spelling
+const DOUBLED_VOWELS: [(&str, &str); 3] = [("ou", "o"), ("oo", "o"), ("uu", "u")];
+
+/// The search form of a title or query: width-folded, kana romanized to Hepburn and long
+/// vowels collapsed, so `しんげき`, `ｼﾝｹﾞｷ` and `Shingeki` all become `shingeki`. Kanji and
+/// other scripts pass through unchanged, and Latin text only loses its macrons.
+#[must_use]
+pub fn normalize(text: &str) -> String {
+    let folded = fold(text);
+    let mut normalized = String::with_capacity(folded.len());
+    let mut rest = folded.as_str();
+    while let Some(first) = rest.chars().next() {
+        let in_kana = kana(first);
+        let end = rest.find(|c| kana(c) != in_kana).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        if in_kana {
+            normalized.push_str(&fold_long_vowels(&romanize(run)));
+        } else {
+            normalized.push_str(run);
+        }
+        rest = tail;
+    }
+    for (variant, vowel) in LONG_VOWELS {
+        normalized = normalized.replace(variant, vowel);
+    }
+    normalized.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Collapses doubled vowels, so Latin spellings such as `Toukyou` and `Ryuu` meet `Tōkyō`
+/// and `Ryū` once both are normalized
+#[must_use]
+pub fn fold_long_vowels(text: &str) -> String {
+    let mut folded = text.to_string();
+    for (variant, vowel) in DOUBLED_VOWELS {
+        folded = folded.replace(variant, vowel);
+    }
+    folded
+}
+
+/// The forms a title or query is indexed and searched under: [`normalize`]d, plus its
+/// [`fold_long_vowels`] variant when that differs, so words like "moon" still match as written
+#[must_use]
+pub fn search_forms(text: &str) -> Vec<String> {
+    let normalized = normalize(text);
+    let folded = fold_long_vowels(&normalized);
+    if folded == normalized {
+        vec![normalized]
+    } else {
+        vec![normalized, folded]
+    }
+}
+
+/// Folds full-width and half-width forms to their usual width, katakana to hiragana and
+/// everything to lowercase
+#[must_use]
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(u32::from(c) - 0xFEE0).unwrap_or(c),
+            '\u{FF61}'..='\u{FF9F}' => {
+                let index = usize::try_from(u32::from(c) - 0xFF61).unwrap_or_default();
+                let kana = HALF_WIDTH_KANA.chars().nth(index).unwrap_or(c);
+                // Half-width voicing marks are separate characters, so fold them into the kana
+                let offset = match chars.peek() {
+                    Some('ﾞ') if voiceable(kana) => 1,
+                    Some('ﾟ') if ('ハ'..='ホ').contains(&kana) => 2,
+                    _ => 0,
+                };
+                if offset > 0 {
+                    chars.next();
+                }
+                if kana == 'ウ' && offset == 1 {
+                    'ヴ'
+                } else {
+                    char::from_u32(u32::from(kana) + offset).unwrap_or(kana)
+                }
+            }
+            _ => c,
+        };
+        let c = match c {
+            'ァ'..='ヶ' => char::from_u32(u32::from(c) - 0x60).unwrap_or(c),
+            _ => c,
+        };
+        folded.extend(c.to_lowercase());
+    }
+    folded
+}
+
+// Hiragana after folding, plus the long vowel mark that romanizes with it
+const fn kana(c: char) -> bool {
+    matches!(c, 'ぁ'..='ゖ' | 'ー')
+}
+
+const fn voiceable(kana: char) -> bool {
+    matches!(kana, 'ウ' | 'カ'..='チ' | 'ツ'..='ト' | 'ハ'..='ホ')
+}
+
+/// Transliterates hiragana to Hepburn romaji, including contracted sounds, the small `っ`
+/// and the `ー` long vowel mark
+#[must_use]
+pub fn romanize(text: &str) -> String {
+    let mut romaji = String::with_capacity(text.len());
+    let mut double_next = false;
+    for c in text.chars() {
+        let Some(syllable) = syllable(c) else {
+            if c == 'ー' {
+                if let Some(vowel) = romaji.chars().last().filter(|c| "aiueo".contains(*c)) {
+                    romaji.push(vowel);
+                }
+            } else {
+                romaji.push(c);
+            }
+            double_next = false;
+            continue;
+        };
+
+        if c == 'っ' {
+            double_next = true;
+            continue;
+        }
+        if double_next {
+            romaji.push(if syllable.starts_with("ch") {
+                't'
+            } else {
+                syllable.chars().next().unwrap_or_default()
+            });
+            double_next = false;
+        }
+
+        // Small kana contract with the syllable before them: きゃ is kya, しゃ sha, ふぁ fa
+        let contracted = match c {
+            'ゃ' | 'ゅ' | 'ょ' if romaji.ends_with('i') => {
+                romaji.pop();
+                if romaji.ends_with("sh") || romaji.ends_with("ch") || romaji.ends_with('j') {
+                    Some(&syllable[1..])
+                } else {
+                    Some(syllable)
+                }
+            }
+            'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ'
+                if romaji.ends_with(|c: char| "aiueo".contains(c)) && romaji.len() > 1 =>
+            {
+                romaji.pop();
+                Some(syllable)
+            }
+            _ => None,
+        };
+        romaji.push_str(contracted.unwrap_or(syllable));
+    }
+    romaji
+}
+
+fn syllable(kana: char) -> Option<&'static str> {
+    HIRAGANA_ROMAJI
+        .binary_search_by_key(&kana, |(kana, _)| *kana)
+        .ok()
+        .map(|index| HIRAGANA_ROMAJI[index].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kana_and_romaji_meet() {
+        assert_eq!(normalize("しんげき"), "shingeki");
+        assert_eq!(normalize("シンゲキ"), "shingeki");
+        assert_eq!(normalize("ｼﾝｹﾞｷ no Kyojin"), "shingeki no kyojin");
+        assert_eq!(normalize("Ｓｈｉｎｇｅｋｉ"), "shingeki");
+    }
+
+    #[test]
+    fn romanizes_gemination_long_marks_and_contractions() {
+        assert_eq!(romanize("がっこう"), "gakkou");
+        assert_eq!(romanize("まっちゃ"), "matcha");
+        assert_eq!(romanize("らーめん"), "raamen");
+        assert_eq!(romanize("しょうじょ"), "shoujo");
+        assert_eq!(romanize("ふぁん"), "fan");
+    }
+
+    #[test]
+    fn long_vowels_meet_across_spellings() {
+        assert_eq!(normalize("とうきょう"), "tokyo");
+        assert_eq!(normalize("Tōkyō"), "tokyo");
+        assert_eq!(search_forms("Toukyou"), ["toukyou", "tokyo"]);
+        assert_eq!(search_forms("Ryuu"), ["ryuu", "ryu"]);
+        assert_eq!(search_forms("Ryū"), ["ryu"]);
+    }
+
+    #[test]
+    fn latin_words_keep_their_spelling() {
+        assert_eq!(normalize("Sailor  Moon"), "sailor moon");
+        assert_eq!(search_forms("moon"), ["moon", "mon"]);
+    }
+}
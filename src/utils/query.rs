@@ -0,0 +1,412 @@
+use crate::{
+    apis::model::{CanonicalMedia, SearchCategory, SearchFilters},
+    errors::error_template::AppError,
+};
+use std::cmp::Ordering;
+use winnow::{
+    ascii::{alpha1, dec_uint, float, multispace0},
+    combinator::{alt, cut_err, delimited, opt, preceded, repeat, separated_pair, terminated},
+    error::{StrContext, StrContextValue},
+    prelude::*,
+    token::take_till,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
+    Score,
+    Popularity,
+    Favorites,
+    Year,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchSort {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl SearchSort {
+    #[must_use]
+    pub fn expression(self) -> String {
+        let field = match self.field {
+            SortField::Score => "score",
+            SortField::Popularity => "popularity",
+            SortField::Favorites => "favorites",
+            SortField::Year => "year",
+        };
+        format!("{field}:{}", if self.descending { "desc" } else { "asc" })
+    }
+
+    pub fn apply(self, media: &mut [CanonicalMedia]) {
+        let value = |media: &CanonicalMedia| match self.field {
+            SortField::Score => media.score.map(f64::from),
+            SortField::Popularity => media.popularity.map(f64::from),
+            SortField::Favorites => media.favorites.map(f64::from),
+            SortField::Year => media.year.map(f64::from),
+        };
+        media.sort_by(|a, b| match (value(a), value(b)) {
+            (Some(a), Some(b)) if self.descending => b.total_cmp(&a),
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedQuery {
+    pub text: String,
+    pub filters: SearchFilters,
+    pub sort: Option<SearchSort>,
+    pub predicates: Vec<String>,
+}
+
+enum Token<'a> {
+    Text(&'a str),
+    Predicate {
+        negated: bool,
+        key: &'a str,
+        value: &'a str,
+        source: &'a str,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Comparison {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+const KEYS: [&str; 8] = [
+    "genre", "year", "season", "status", "type", "rating", "score", "sort",
+];
+
+/// Splits a query such as `frieren genre:fantasy -genre:romance year:>=2020 sort:popularity`
+/// into its free text and typed predicates. Words with an unknown `key:` prefix stay part of
+/// the free text so titles like `Re:Zero` keep working.
+pub fn parse_query(input: &str, category: SearchCategory) -> Result<ParsedQuery, AppError> {
+    let tokens = query_tokens.parse(input).map_err(|e| {
+        AppError::InvalidData(format!(
+            "{} at position {}",
+            e.inner().to_string().replace('\n', ", "),
+            e.offset() + 1
+        ))
+    })?;
+
+    let mut parsed = ParsedQuery::default();
+    let mut text = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Predicate {
+                negated,
+                key,
+                value,
+                ..
+            } if KEYS.contains(&key.to_lowercase().as_str()) => {
+                apply_predicate(&mut parsed, category, negated, &key.to_lowercase(), value)?;
+                parsed.predicates.push(token.source());
+            }
+            Token::Predicate { .. } | Token::Text(_) => text.push(token.source()),
+        }
+    }
+    parsed.text = text.join(" ");
+
+    Ok(parsed)
+}
+
+impl Token<'_> {
+    // The input as typed, quotes included, so predicates rebuilt into a query parse the same
+    fn source(&self) -> String {
+        match self {
+            Self::Text(source) | Self::Predicate { source, .. } => (*source).to_string(),
+        }
+    }
+}
+
+fn apply_predicate(
+    parsed: &mut ParsedQuery,
+    category: SearchCategory,
+    negated: bool,
+    key: &str,
+    value: &str,
+) -> Result<(), AppError> {
+    let invalid = |expected: &str| {
+        AppError::InvalidData(format!(
+            "Invalid value `{value}` for `{key}:`, expected {expected}"
+        ))
+    };
+    if value.is_empty() {
+        return Err(invalid("a value after the colon"));
+    }
+    if negated && key != "genre" {
+        return Err(AppError::InvalidData(format!(
+            "Only genres can be excluded, `-{key}:` is not supported"
+        )));
+    }
+
+    let filters = &mut parsed.filters;
+    match key {
+        "genre" => {
+            let genres = value
+                .split(',')
+                .filter(|genre| !genre.is_empty())
+                .map(str::to_string);
+            if negated {
+                filters.exclude_genres.extend(genres);
+            } else {
+                filters.genres.extend(genres);
+            }
+        }
+        "year" => {
+            let (from, to) = year_range
+                .parse(value)
+                .map_err(|_| invalid("a year such as 2020, >=2015 or 2010..2019"))?;
+            filters.year_from = from.or(filters.year_from);
+            filters.year_to = to.or(filters.year_to);
+        }
+        "score" => {
+            filters.min_score = Some(
+                min_score
+                    .parse(value)
+                    .ok()
+                    .flatten()
+                    .ok_or_else(|| invalid("a minimum score such as 7, >=7.5 or >8"))?,
+            );
+        }
+        "season" => {
+            let season = value.to_lowercase();
+            if !["winter", "spring", "summer", "fall"].contains(&season.as_str()) {
+                return Err(invalid("one of winter, spring, summer or fall"));
+            }
+            filters.season = Some(season);
+        }
+        "status" => filters.status = Some(status_alias(category, value)),
+        "type" => filters.media_type = Some(value.to_string()),
+        "rating" => filters.rating = Some(value.to_string()),
+        _ => {
+            parsed.sort = Some(sort.parse(value).map_err(|_| {
+                invalid(
+                    "score, popularity, favorites or year, optionally followed by :asc or :desc",
+                )
+            })?);
+        }
+    }
+
+    Ok(())
+}
+
+fn status_alias(category: SearchCategory, status: &str) -> String {
+    let manga = category == SearchCategory::Manga;
+    match (status.to_lowercase().as_str(), manga) {
+        ("airing" | "publishing" | "releasing", false) => "Currently Airing",
+        ("airing" | "publishing" | "releasing", true) => "Publishing",
+        ("finished" | "completed", false) => "Finished Airing",
+        ("finished" | "completed", true) => "Finished",
+        ("upcoming", false) => "Not yet aired",
+        ("upcoming", true) => "Not yet published",
+        ("hiatus", _) => "On Hiatus",
+        _ => status,
+    }
+    .to_string()
+}
+
+fn query_tokens<'a>(input: &mut &'a str) -> ModalResult<Vec<Token<'a>>> {
+    terminated(repeat(0.., preceded(multispace0, token)), multispace0).parse_next(input)
+}
+
+fn token<'a>(input: &mut &'a str) -> ModalResult<Token<'a>> {
+    alt((
+        predicate,
+        quoted.take().map(Token::Text),
+        word.map(Token::Text),
+    ))
+    .parse_next(input)
+}
+
+fn predicate<'a>(input: &mut &'a str) -> ModalResult<Token<'a>> {
+    (opt('-'), alpha1, ':', alt((quoted, word, "")))
+        .with_taken()
+        .map(|((negated, key, _, value), source)| Token::Predicate {
+            negated: negated.is_some(),
+            key,
+            value,
+            source,
+        })
+        .parse_next(input)
+}
+
+fn quoted<'a>(input: &mut &'a str) -> ModalResult<&'a str> {
+    delimited(
+        '"',
+        take_till(0.., '"'),
+        cut_err('"')
+            .context(StrContext::Label("quoted phrase"))
+            .context(StrContext::Expected(StrContextValue::CharLiteral('"'))),
+    )
+    .parse_next(input)
+}
+
+fn word<'a>(input: &mut &'a str) -> ModalResult<&'a str> {
+    take_till(1.., char::is_whitespace).parse_next(input)
+}
+
+fn comparison(input: &mut &str) -> ModalResult<Comparison> {
+    alt((
+        ">=".value(Comparison::Ge),
+        "<=".value(Comparison::Le),
+        '>'.value(Comparison::Gt),
+        '<'.value(Comparison::Lt),
+        '='.value(Comparison::Eq),
+    ))
+    .parse_next(input)
+}
+
+fn year_range(input: &mut &str) -> ModalResult<(Option<i32>, Option<i32>)> {
+    alt((
+        separated_pair(dec_uint, "..", dec_uint)
+            .map(|(from, to): (u16, u16)| (Some(i32::from(from)), Some(i32::from(to)))),
+        (opt(comparison), dec_uint).map(|(comparison, year): (_, u16)| {
+            let year = i32::from(year);
+            match comparison.unwrap_or(Comparison::Eq) {
+                Comparison::Eq => (Some(year), Some(year)),
+                Comparison::Ge => (Some(year), None),
+                Comparison::Gt => (Some(year + 1), None),
+                Comparison::Le => (None, Some(year)),
+                Comparison::Lt => (None, Some(year - 1)),
+            }
+        }),
+    ))
+    .parse_next(input)
+}
+
+fn min_score(input: &mut &str) -> ModalResult<Option<f32>> {
+    // winnow's float also reads nan and inf, which no score can be compared against
+    (
+        opt(comparison),
+        float.verify(|score: &f32| score.is_finite()),
+    )
+        .map(
+            |(comparison, score): (_, f32)| match comparison.unwrap_or(Comparison::Ge) {
+                Comparison::Eq | Comparison::Ge => Some(score),
+                Comparison::Gt => Some(score.next_up()),
+                Comparison::Lt | Comparison::Le => None,
+            },
+        )
+        .parse_next(input)
+}
+
+fn sort(input: &mut &str) -> ModalResult<SearchSort> {
+    (
+        alt((
+            "score".value(SortField::Score),
+            "popularity".value(SortField::Popularity),
+            "favorites".value(SortField::Favorites),
+            "year".value(SortField::Year),
+        )),
+        opt(preceded(':', alt(("asc".value(false), "desc".value(true))))),
+    )
+        .map(|(field, descending)| SearchSort {
+            field,
+            descending: descending.unwrap_or(true),
+        })
+        .parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_values_and_negation() {
+        let parsed = parse_query(
+            r#"genre:"slice of life" -genre:romance"#,
+            SearchCategory::Anime,
+        )
+        .unwrap();
+        assert_eq!(parsed.filters.genres, ["slice of life"]);
+        assert_eq!(parsed.filters.exclude_genres, ["romance"]);
+        assert!(parsed.text.is_empty());
+    }
+
+    #[test]
+    fn unknown_keys_stay_text() {
+        let parsed = parse_query("Re:Zero genre:fantasy", SearchCategory::Anime).unwrap();
+        assert_eq!(parsed.text, "Re:Zero");
+        assert_eq!(parsed.predicates, ["genre:fantasy"]);
+    }
+
+    #[test]
+    fn year_ranges() {
+        let range = |query| {
+            let filters = parse_query(query, SearchCategory::Anime).unwrap().filters;
+            (filters.year_from, filters.year_to)
+        };
+        assert_eq!(range("year:2020..2022"), (Some(2020), Some(2022)));
+        assert_eq!(range("year:2020"), (Some(2020), Some(2020)));
+        assert_eq!(range("year:>2019"), (Some(2020), None));
+        assert_eq!(range("year:<=2015"), (None, Some(2015)));
+    }
+
+    #[test]
+    fn scores_must_be_finite() {
+        let parsed = parse_query("score:>=7.5", SearchCategory::Anime).unwrap();
+        assert_eq!(parsed.filters.min_score, Some(7.5));
+        assert!(parse_query("score:nan", SearchCategory::Anime).is_err());
+        assert!(parse_query("score:inf", SearchCategory::Anime).is_err());
+    }
+
+    #[test]
+    fn unterminated_quote_reports_its_position() {
+        let Err(AppError::InvalidData(message)) =
+            parse_query(r#"frieren genre:"slice"#, SearchCategory::Anime)
+        else {
+            panic!("an unterminated quote parsed");
+        };
+        assert!(message.ends_with("at position 21"), "{message}");
+    }
+
+    #[test]
+    fn sort_defaults_to_descending() {
+        let sort = |query| parse_query(query, SearchCategory::Anime).unwrap().sort;
+        assert_eq!(
+            sort("sort:popularity"),
+            Some(SearchSort {
+                field: SortField::Popularity,
+                descending: true,
+            })
+        );
+        assert_eq!(
+            sort("sort:year:asc"),
+            Some(SearchSort {
+                field: SortField::Year,
+                descending: false,
+            })
+        );
+    }
+
+    #[test]
+    fn predicates_rebuild_the_same_query() {
+        let parsed = parse_query(
+            r#"frieren genre:"slice of life" -genre:romance"#,
+            SearchCategory::Anime,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.predicates,
+            [r#"genre:"slice of life""#, "-genre:romance"]
+        );
+
+        let rebuilt = std::iter::once(parsed.text.clone())
+            .chain(parsed.predicates.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(
+            parse_query(&rebuilt, SearchCategory::Anime).unwrap(),
+            parsed
+        );
+    }
+}
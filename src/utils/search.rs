@@ -1,20 +1,464 @@
-use crate::apis::jikan::anime::model::Anime;
+use crate::apis::model::{
+    SearchCategory, SearchFilters, SearchResponse, Suggestion, TrendingSearch,
+};
+use cfg_if::cfg_if;
 use leptos::prelude::*;
 
+cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::{
+            apis::{
+                model::{
+                    CanonicalMedia, Character, Media, MediaKind, Person, Provider, SearchGroup,
+                    SearchResult,
+                },
+                provider::SearchHits,
+            },
+            config::consts::{
+                DID_YOU_MEAN_THRESHOLD, SEARCH_EVENT_RESULT_KEY_MAX_LENGTH, SEARCH_PAGE_SIZE,
+                SEARCH_SESSION_EVENTS_KEY, SEARCH_SESSION_EVENTS_MAX, TRENDING_MIN_SEARCHES,
+                TRENDING_SEARCHES_LIMIT, TRENDING_WINDOW_DAYS,
+            },
+            db::backend::PostgreSQLBackend,
+            errors::error_template::AppError,
+            utils::{
+                cache::{SEARCH_CACHE, SearchKey, VOCABULARY_CACHE},
+                query::{ParsedQuery, SearchSort, parse_query},
+                search_backend::{
+                    FacetCounts, RankedGroup, SearchBackend, count_facets, rank_by_position,
+                    search_backends,
+                },
+                spelling::Vocabulary,
+            },
+        };
+        use axum_login::AuthSession;
+        use futures::try_join;
+        use std::{collections::HashMap, sync::Arc};
+        use tower_sessions::Session;
+        use tracing::warn;
+
+        pub async fn search_media(
+            backends: &[Box<dyn SearchBackend>],
+            postgres: Option<&PostgreSQLBackend>,
+            provider: Provider,
+            kind: MediaKind,
+            query: &str,
+            filters: &SearchFilters,
+            sort: Option<SearchSort>,
+            page: i32,
+        ) -> Result<(SearchHits<CanonicalMedia>, FacetCounts), AppError> {
+            for backend in backends {
+                match backend.search_media(kind, query, filters, sort, page).await {
+                    Ok(results) => return Ok(results),
+                    Err(e) => warn!("{} media search failed, failing over: {e}", backend.name()),
+                }
+            }
+
+            let hits = provider.metadata().search_media(kind, query, page).await?;
+            let canonical_ids = match postgres {
+                Some(postgres) => canonical_ids(postgres, provider, kind, &hits.items)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Resolving canonical ids failed: {e}");
+                        HashMap::new()
+                    }),
+                None => HashMap::new(),
+            };
+            let mut media: Vec<CanonicalMedia> = hits
+                .items
+                .into_iter()
+                .filter_map(|media| {
+                    // Canonical ids start at 1, so 0 marks media the catalog doesn't have yet
+                    let id = canonical_ids.get(&media.id).copied().unwrap_or(0);
+                    CanonicalMedia::merge(id, &[media])
+                })
+                .filter(|media| filters.matches(media))
+                .collect();
+            if let Some(sort) = sort {
+                sort.apply(&mut media);
+            }
+            let facets = count_facets(&media);
+            Ok((
+                SearchHits {
+                    items: media,
+                    has_next_page: hits.has_next_page,
+                    total: hits.total,
+                },
+                facets,
+            ))
+        }
+
+        async fn canonical_ids(
+            postgres: &PostgreSQLBackend,
+            provider: Provider,
+            kind: MediaKind,
+            media: &[Media],
+        ) -> Result<HashMap<i32, i64>, AppError> {
+            let ids: Vec<i32> = media.iter().map(|media| media.id).collect();
+            Ok(postgres
+                .linked_identities(provider, kind.into(), &ids)
+                .await?
+                .into_iter()
+                .flat_map(|identity| {
+                    identity
+                        .ids
+                        .into_iter()
+                        .filter(|id| id.provider == provider)
+                        .map(move |id| (id.id, identity.canonical_id))
+                })
+                .collect())
+        }
+
+        pub async fn search_characters(
+            backends: &[Box<dyn SearchBackend>],
+            provider: Provider,
+            query: &str,
+            page: i32,
+        ) -> Result<SearchHits<Character>, AppError> {
+            for backend in backends {
+                match backend.search_characters(provider, query, page).await {
+                    Ok(results) => return Ok(results),
+                    Err(e) => {
+                        warn!("{} character search failed, failing over: {e}", backend.name());
+                    }
+                }
+            }
+
+            provider.metadata().search_characters(query, page).await
+        }
+
+        pub async fn search_people(
+            provider: Provider,
+            query: &str,
+            page: i32,
+        ) -> Result<SearchHits<Person>, AppError> {
+            provider.metadata().search_people(query, page).await
+        }
+
+        pub async fn search_all(
+            backends: &[Box<dyn SearchBackend>],
+            postgres: Option<&PostgreSQLBackend>,
+            provider: Provider,
+            query: &str,
+            filters: &SearchFilters,
+            page: i32,
+        ) -> Result<SearchResponse, AppError> {
+            for backend in backends {
+                match backend.search_all(provider, query, filters, page).await {
+                    Ok(groups) => return Ok(federate(groups, page)),
+                    Err(e) => {
+                        warn!("{} federated search failed, failing over: {e}", backend.name());
+                    }
+                }
+            }
+
+            let ((anime, _), (manga, _), characters) = try_join!(
+                search_media(&[], postgres, provider, MediaKind::Anime, query, filters, None, page),
+                search_media(&[], postgres, provider, MediaKind::Manga, query, filters, None, page),
+                search_characters(&[], provider, query, page),
+            )?;
+            Ok(federate(
+                vec![
+                    rank_by_position(SearchCategory::Anime, anime.map(SearchResult::Anime)),
+                    rank_by_position(SearchCategory::Manga, manga.map(SearchResult::Manga)),
+                    rank_by_position(
+                        SearchCategory::Character,
+                        characters.map(SearchResult::Character),
+                    ),
+                ],
+                page,
+            ))
+        }
+
+        // Groups are ordered by their best hit, so the type that matches the query best
+        // comes first
+        fn federate(groups: Vec<RankedGroup>, page: i32) -> SearchResponse {
+            let total_hits = groups
+                .iter()
+                .map(|(_, hits)| hits.total)
+                .sum::<Option<usize>>();
+            let total_pages = groups
+                .iter()
+                .filter_map(|(_, hits)| hits.total)
+                .map(|total| total.div_ceil(SEARCH_PAGE_SIZE))
+                .max();
+            let has_next_page = groups.iter().any(|(_, hits)| hits.has_next_page);
+
+            let mut groups: Vec<(f64, SearchGroup)> = groups
+                .into_iter()
+                .filter(|(_, hits)| !hits.items.is_empty())
+                .map(|(category, hits)| {
+                    let mut items = hits.items;
+                    items.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                    let best = items.first().map_or(0.0, |(score, _)| *score);
+                    (
+                        best,
+                        SearchGroup {
+                            category,
+                            total: hits.total,
+                            results: items.into_iter().map(|(_, result)| result).collect(),
+                        },
+                    )
+                })
+                .collect();
+            groups.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+            SearchResponse {
+                results: Vec::new(),
+                facets: FacetCounts::new(),
+                page: page.max(1),
+                total_hits,
+                total_pages,
+                has_next_page,
+                groups: groups.into_iter().map(|(_, group)| group).collect(),
+                corrections: Vec::new(),
+                event_id: None,
+            }
+        }
+
+        pub async fn search(
+            backends: &[Box<dyn SearchBackend>],
+            postgres: Option<&PostgreSQLBackend>,
+            provider: Provider,
+            category: SearchCategory,
+            query: &str,
+            filters: &SearchFilters,
+            sort: Option<SearchSort>,
+            page: i32,
+        ) -> Result<SearchResponse, AppError> {
+            let (hits, facets) = match category {
+                SearchCategory::All => {
+                    return search_all(backends, postgres, provider, query, filters, page).await;
+                }
+                SearchCategory::Anime => {
+                    let (media, facets) = search_media(
+                        backends,
+                        postgres,
+                        provider,
+                        MediaKind::Anime,
+                        query,
+                        filters,
+                        sort,
+                        page,
+                    )
+                    .await?;
+                    (media.map(SearchResult::Anime), facets)
+                }
+                SearchCategory::Manga => {
+                    let (media, facets) = search_media(
+                        backends,
+                        postgres,
+                        provider,
+                        MediaKind::Manga,
+                        query,
+                        filters,
+                        sort,
+                        page,
+                    )
+                    .await?;
+                    (media.map(SearchResult::Manga), facets)
+                }
+                SearchCategory::Character => (
+                    search_characters(backends, provider, query, page)
+                        .await?
+                        .map(SearchResult::Character),
+                    FacetCounts::new(),
+                ),
+                SearchCategory::People => (
+                    search_people(provider, query, page)
+                        .await?
+                        .map(SearchResult::People),
+                    FacetCounts::new(),
+                ),
+            };
+
+            Ok(SearchResponse {
+                results: hits.items,
+                facets,
+                page: page.max(1),
+                total_hits: hits.total,
+                total_pages: hits.total.map(|total| total.div_ceil(SEARCH_PAGE_SIZE)),
+                has_next_page: hits.has_next_page,
+                groups: Vec::new(),
+                corrections: Vec::new(),
+                event_id: None,
+            })
+        }
+
+        // Suggestions run on every keystroke, so they never fall back to the rate-limited
+        // provider APIs
+        pub async fn suggestions(
+            backends: &[Box<dyn SearchBackend>],
+            provider: Provider,
+            query: &str,
+        ) -> Vec<Suggestion> {
+            for backend in backends {
+                match backend.suggestions(provider, query).await {
+                    Ok(suggestions) => return suggestions,
+                    Err(e) => warn!("{} suggestions failed, failing over: {e}", backend.name()),
+                }
+            }
+
+            Vec::new()
+        }
+
+        // Corrections keep the query's predicates, so following one reruns the same search
+        // with only the text fixed
+        pub async fn did_you_mean(
+            backend: &PostgreSQLBackend,
+            parsed: &ParsedQuery,
+        ) -> Result<Vec<String>, AppError> {
+            let vocabulary = VOCABULARY_CACHE
+                .get_or_try_insert((), async {
+                    Ok(Arc::new(Vocabulary::load(backend).await?))
+                })
+                .await?;
+
+            Ok(vocabulary
+                .corrections(&parsed.text)
+                .into_iter()
+                .map(|correction| {
+                    std::iter::once(correction)
+                        .chain(parsed.predicates.iter().cloned())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect())
+        }
+
+        // The events stay out of the analytics table's rows, so a click is tied to the session
+        // that ran the search without the table ever holding who ran it
+        async fn session_search_events(session: &Session) -> Result<Vec<i64>, AppError> {
+            session
+                .get(SEARCH_SESSION_EVENTS_KEY)
+                .await
+                .map(Option::unwrap_or_default)
+                .map_err(|e| AppError::InternalError(format!("Load search events: {e}")))
+        }
+
+        async fn remember_search_event(session: &Session, event_id: i64) -> Result<(), AppError> {
+            let mut events = session_search_events(session).await?;
+            events.push(event_id);
+            let excess = events.len().saturating_sub(SEARCH_SESSION_EVENTS_MAX);
+            events.drain(..excess);
+            session
+                .insert(SEARCH_SESSION_EVENTS_KEY, events)
+                .await
+                .map_err(|e| AppError::InternalError(format!("Store search events: {e}")))
+        }
+
+        fn session_backends() -> Vec<Box<dyn SearchBackend>> {
+            search_backends(
+                use_context::<AuthSession<PostgreSQLBackend>>().map(|session| session.backend),
+            )
+        }
+    }
+}
+
+#[server]
+pub async fn search_engine(
+    category: SearchCategory,
+    query: String,
+    filters: SearchFilters,
+    page: i32,
+) -> Result<SearchResponse, ServerFnError> {
+    let parsed = parse_query(&query, category)?;
+    let filters = filters.merge(parsed.filters.clone());
+    let backends = session_backends();
+    let postgres = use_context::<AuthSession<PostgreSQLBackend>>().map(|session| session.backend);
+    let session = use_context::<Session>();
+    // The sort is part of the raw query, so key on it rather than the parsed text alone
+    let key = SearchKey::new(category, &query, &filters, page);
+    let mut response = SEARCH_CACHE
+        .get_or_try_insert(key.clone(), async {
+            let mut response = search(
+                &backends,
+                postgres.as_ref(),
+                Provider::default(),
+                category,
+                &parsed.text,
+                &filters,
+                parsed.sort,
+                page,
+            )
+            .await?;
+            if page <= 1
+                && !parsed.text.is_empty()
+                && response.result_count() <= DID_YOU_MEAN_THRESHOLD
+                && let Some(postgres) = &postgres
+            {
+                response.corrections = did_you_mean(postgres, &parsed).await.unwrap_or_else(|e| {
+                    warn!("Spelling suggestions failed: {e}");
+                    Vec::new()
+                });
+            }
+            Ok(response)
+        })
+        .await?;
+
+    // Recorded outside the cache so cached searches still count, and only the first page
+    // since later pages continue the same search
+    if page <= 1
+        && !key.query.is_empty()
+        && let Some(postgres) = &postgres
+    {
+        let result_count = response
+            .total_hits
+            .unwrap_or_else(|| response.result_count());
+        match postgres
+            .record_search_event(&key.query, category, &key.filters, result_count)
+            .await
+        {
+            Ok(event_id) => match &session {
+                Some(session) => match remember_search_event(session, event_id).await {
+                    Ok(()) => response.event_id = Some(event_id),
+                    Err(e) => warn!("Remembering search event failed: {e}"),
+                },
+                None => warn!("No session to remember search event {event_id} in"),
+            },
+            Err(e) => warn!("Recording search event failed: {e}"),
+        }
+    }
+
+    Ok(response)
+}
+
+#[server]
+pub async fn record_search_click(event_id: i64, result: String) -> Result<(), ServerFnError> {
+    if result.len() > SEARCH_EVENT_RESULT_KEY_MAX_LENGTH {
+        return Err(AppError::InvalidData("Invalid search result".to_string()).into());
+    }
+    let auth_session: AuthSession<PostgreSQLBackend> =
+        use_context().expect("auth session not provided");
+    let session: Session = use_context().expect("session not provided");
+    if !session_search_events(&session).await?.contains(&event_id) {
+        return Err(
+            AppError::InvalidSessionId("Search event not from this session".to_string()).into(),
+        );
+    }
+    auth_session
+        .backend
+        .record_search_click(event_id, &result)
+        .await?;
+
+    Ok(())
+}
+
+#[server]
+pub async fn trending_searches() -> Result<Vec<TrendingSearch>, ServerFnError> {
+    let session: AuthSession<PostgreSQLBackend> = use_context().expect("session not provided");
+
+    Ok(session
+        .backend
+        .trending_searches(
+            TRENDING_WINDOW_DAYS,
+            TRENDING_MIN_SEARCHES,
+            TRENDING_SEARCHES_LIMIT,
+        )
+        .await?)
+}
+
 #[server]
-pub async fn search_engine(query: String) -> Result<Vec<Anime>, ServerFnError> {
-    use crate::apis::jikan::anime::fetch::*;
-    /*  let results = if let Some(category) = category {
-        match category.as_str() {
-            "anime" => search_anime(query).await.unwrap(),
-            "manga" => search_manga(query).await.unwrap(),
-            "character" => search_character(query).await.unwrap(),
-            _ => search_anime(query).await.unwrap(),
-        }
-    } else {
-        search_anime(query).await.unwrap()
-    };
-    Ok(results) */
-
-    Ok(search_anime(query).await.unwrap())
+pub async fn search_suggestions(query: String) -> Result<Vec<Suggestion>, ServerFnError> {
+    Ok(suggestions(&session_backends(), Provider::default(), query.trim()).await)
 }
@@ -0,0 +1,700 @@
+use crate::{
+    apis::{
+        indexer::{linked_ids, merge_records},
+        model::{
+            CanonicalMedia, Character, Entity, Highlight, HighlightFragment, MEDIA_FACETS,
+            MediaKind, MediaTitle, Provider, SearchCategory, SearchFilters, SearchResult,
+            Suggestion, canonical_index_name, index_name,
+        },
+        provider::SearchHits,
+    },
+    config::{
+        consts::{
+            MAX_HIGHLIGHTS, POSTGRES_SEARCH_CANDIDATES, SEARCH_PAGE_SIZE, SNIPPET_CROP_WORDS,
+            SUGGESTIONS_PER_CATEGORY,
+        },
+        settings::SearchBackendKind,
+        types::{MEILISEARCH_CLIENT, UTILS_CONFIG},
+    },
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+    utils::{
+        cache::{catalog_characters, catalog_media},
+        normalize::search_forms,
+        query::SearchSort,
+    },
+};
+use async_trait::async_trait;
+use futures::try_join;
+use meilisearch_sdk::{
+    client::Client,
+    request::HttpClient,
+    search::{SearchQuery, SearchResult as SearchResultHit, Selectors},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, future::Future, time::Duration};
+use tokio::time::timeout;
+
+pub type FacetCounts = BTreeMap<String, BTreeMap<String, usize>>;
+
+// Private use characters never occur in catalog text, unlike HTML tags in synopses
+const HIGHLIGHT_PRE_TAG: &str = "\u{E000}";
+const HIGHLIGHT_POST_TAG: &str = "\u{E001}";
+const MEDIA_HIGHLIGHTED: [&str; 3] = ["titles", "synonyms", "synopsis"];
+const CHARACTER_HIGHLIGHTED: [&str; 3] = ["name_native", "nicknames", "about"];
+const CROPPED: [(&str, Option<usize>); 2] = [("synopsis", None), ("about", None)];
+
+/// One page of a single index in an "all" search, each hit carrying the score used to rank
+/// it against the other indexes
+pub type RankedGroup = (SearchCategory, SearchHits<(f64, SearchResult)>);
+
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        filters: &SearchFilters,
+        sort: Option<SearchSort>,
+        page: i32,
+    ) -> Result<(SearchHits<CanonicalMedia>, FacetCounts), AppError>;
+
+    async fn search_characters(
+        &self,
+        provider: Provider,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError>;
+
+    async fn suggestions(
+        &self,
+        provider: Provider,
+        query: &str,
+    ) -> Result<Vec<Suggestion>, AppError>;
+
+    async fn search_all(
+        &self,
+        provider: Provider,
+        query: &str,
+        filters: &SearchFilters,
+        page: i32,
+    ) -> Result<Vec<RankedGroup>, AppError> {
+        let ((anime, _), (manga, _), characters) = try_join!(
+            self.search_media(MediaKind::Anime, query, filters, None, page),
+            self.search_media(MediaKind::Manga, query, filters, None, page),
+            self.search_characters(provider, query, page),
+        )?;
+
+        Ok(vec![
+            rank_by_position(SearchCategory::Anime, anime.map(SearchResult::Anime)),
+            rank_by_position(SearchCategory::Manga, manga.map(SearchResult::Manga)),
+            rank_by_position(
+                SearchCategory::Character,
+                characters.map(SearchResult::Character),
+            ),
+        ])
+    }
+}
+
+/// The configured backend first, then Postgres as the failover when Meilisearch errors
+/// or doesn't answer in time.
+#[must_use]
+pub fn search_backends(postgres: Option<PostgreSQLBackend>) -> Vec<Box<dyn SearchBackend>> {
+    let config = UTILS_CONFIG.get().map(|config| config.search.clone());
+    let mut backends: Vec<Box<dyn SearchBackend>> = Vec::new();
+    if config
+        .as_ref()
+        .is_none_or(|config| config.backend == SearchBackendKind::Meilisearch)
+        && let Some(client) = MEILISEARCH_CLIENT.get()
+    {
+        backends.push(Box::new(MeilisearchSearch {
+            client: client.as_ref(),
+            timeout: Duration::from_millis(
+                config
+                    .as_ref()
+                    .map_or(2000, |config| config.meilisearch_timeout_ms),
+            ),
+        }));
+    }
+    if let Some(backend) = postgres {
+        backends.push(Box::new(PostgresSearch { backend }));
+    }
+
+    backends
+}
+
+// Both search forms, the long-vowel folded one first: Meilisearch drops words from the end
+// when too few documents match them all, and every spelling of a title indexes the folded form
+fn search_query(query: &str) -> String {
+    let mut forms = search_forms(query);
+    forms.reverse();
+    forms.join(" ")
+}
+
+pub fn page_offset(page: i32) -> usize {
+    usize::try_from(page.max(1) - 1).unwrap_or_default() * SEARCH_PAGE_SIZE
+}
+
+pub fn count_facets(media: &[CanonicalMedia]) -> FacetCounts {
+    let mut facets = FacetCounts::new();
+    for m in media {
+        let values = m
+            .genres
+            .iter()
+            .map(|genre| ("genres", genre.clone()))
+            .chain(
+                [
+                    ("year", m.year.map(|year| year.to_string())),
+                    ("season", m.season.clone()),
+                    ("status", m.status.clone()),
+                    ("type", m.media_type.clone()),
+                    ("rating", m.rating.clone()),
+                ]
+                .into_iter()
+                .filter_map(|(facet, value)| value.map(|value| (facet, value))),
+            );
+        for (facet, value) in values {
+            *facets
+                .entry(facet.to_string())
+                .or_default()
+                .entry(value)
+                .or_default() += 1;
+        }
+    }
+    facets
+}
+
+/// Backends without comparable relevance scores interleave the indexes by rank instead
+pub fn rank_by_position(category: SearchCategory, hits: SearchHits<SearchResult>) -> RankedGroup {
+    let items = hits
+        .items
+        .into_iter()
+        .zip(1..)
+        .map(|(result, rank): (_, u32)| (1.0 / f64::from(rank), result))
+        .collect();
+    (
+        category,
+        SearchHits {
+            items,
+            has_next_page: hits.has_next_page,
+            total: hits.total,
+        },
+    )
+}
+
+fn media_suggestions(
+    category: SearchCategory,
+    media: impl IntoIterator<Item = (Vec<MediaTitle>, Option<String>)>,
+) -> impl Iterator<Item = Suggestion> {
+    media.into_iter().filter_map(move |(titles, image_url)| {
+        Some(Suggestion {
+            category,
+            title: titles.into_iter().next()?.title,
+            image_url,
+        })
+    })
+}
+
+fn character_suggestions(
+    characters: impl IntoIterator<Item = (String, Option<String>)>,
+) -> impl Iterator<Item = Suggestion> {
+    characters.into_iter().map(|(name, image_url)| Suggestion {
+        category: SearchCategory::Character,
+        title: name,
+        image_url,
+    })
+}
+
+trait Highlighted: DeserializeOwned {
+    const FIELDS: &'static [&'static str];
+
+    fn set_highlights(&mut self, highlights: Vec<Highlight>);
+}
+
+impl Highlighted for CanonicalMedia {
+    const FIELDS: &'static [&'static str] = &MEDIA_HIGHLIGHTED;
+
+    fn set_highlights(&mut self, highlights: Vec<Highlight>) {
+        self.highlights = highlights;
+    }
+}
+
+impl Highlighted for Character {
+    const FIELDS: &'static [&'static str] = &CHARACTER_HIGHLIGHTED;
+
+    fn set_highlights(&mut self, highlights: Vec<Highlight>) {
+        self.highlights = highlights;
+    }
+}
+
+fn with_highlights<Http: HttpClient>(
+    search: &mut SearchQuery<'_, Http>,
+    fields: &'static [&'static str],
+) {
+    search
+        .with_attributes_to_highlight(Selectors::Some(fields))
+        .with_attributes_to_crop(Selectors::Some(&CROPPED))
+        .with_crop_length(SNIPPET_CROP_WORDS)
+        .with_highlight_pre_tag(HIGHLIGHT_PRE_TAG)
+        .with_highlight_post_tag(HIGHLIGHT_POST_TAG);
+}
+
+// The first title is already the card heading, so only alternate titles are worth showing
+fn highlights(formatted: Option<&Map<String, Value>>, fields: &[&str]) -> Vec<Highlight> {
+    let Some(formatted) = formatted else {
+        return Vec::new();
+    };
+    fields
+        .iter()
+        .flat_map(|field| {
+            let values: Vec<&Value> = match formatted.get(*field) {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .skip(usize::from(*field == "titles"))
+                    .collect(),
+                Some(value) => vec![value],
+                None => Vec::new(),
+            };
+            values
+                .into_iter()
+                .filter_map(|value| value.get("title").unwrap_or(value).as_str())
+                .filter(|text| text.contains(HIGHLIGHT_PRE_TAG))
+                .map(move |text| highlight(field, text))
+        })
+        .take(MAX_HIGHLIGHTS)
+        .collect()
+}
+
+fn highlight(field: &str, formatted: &str) -> Highlight {
+    let mut fragments = Vec::new();
+    let mut push = |text: &str, matched: bool| {
+        if !text.is_empty() {
+            fragments.push(HighlightFragment {
+                text: text.to_string(),
+                matched,
+            });
+        }
+    };
+    let mut parts = formatted.split(HIGHLIGHT_PRE_TAG);
+    push(parts.next().unwrap_or_default(), false);
+    for part in parts {
+        let (matched, rest) = part.split_once(HIGHLIGHT_POST_TAG).unwrap_or((part, ""));
+        push(matched, true);
+        push(rest, false);
+    }
+
+    Highlight {
+        field: field.to_string(),
+        fragments,
+    }
+}
+
+fn decode<T: Highlighted>(hit: SearchResultHit<Value>) -> Result<T, serde_json::Error> {
+    let mut result: T = serde_json::from_value(hit.result)?;
+    result.set_highlights(highlights(hit.formatted_result.as_ref(), T::FIELDS));
+    Ok(result)
+}
+
+pub struct MeilisearchSearch {
+    client: &'static Client,
+    timeout: Duration,
+}
+
+#[derive(Deserialize)]
+struct MediaSuggestion {
+    titles: Vec<MediaTitle>,
+    image_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CharacterSuggestion {
+    name: String,
+    image_url: Option<String>,
+}
+
+impl MeilisearchSearch {
+    async fn respond<T>(
+        &self,
+        index: &str,
+        request: impl Future<Output = Result<T, meilisearch_sdk::errors::Error>>,
+    ) -> Result<T, AppError> {
+        timeout(self.timeout, request)
+            .await
+            .map_err(|_| AppError::SearchError(format!("Meilisearch timed out searching {index}")))?
+            .map_err(|e| AppError::SearchError(format!("Search {index}: {e}")))
+    }
+
+    fn hits<T: Highlighted>(
+        hits: Vec<SearchResultHit<T>>,
+        offset: usize,
+        estimated_total_hits: Option<usize>,
+    ) -> SearchHits<T> {
+        let items: Vec<T> = hits
+            .into_iter()
+            .map(|hit| {
+                let mut result = hit.result;
+                result.set_highlights(highlights(hit.formatted_result.as_ref(), T::FIELDS));
+                result
+            })
+            .collect();
+        SearchHits {
+            has_next_page: estimated_total_hits.is_some_and(|total| offset + items.len() < total),
+            total: estimated_total_hits,
+            items,
+        }
+    }
+
+    fn search_result(
+        category: SearchCategory,
+        hit: SearchResultHit<Value>,
+    ) -> Result<SearchResult, AppError> {
+        let result = match category {
+            SearchCategory::Anime => decode(hit).map(SearchResult::Anime),
+            SearchCategory::Manga => decode(hit).map(SearchResult::Manga),
+            _ => decode(hit).map(SearchResult::Character),
+        };
+        result.map_err(|e| AppError::SearchError(format!("Decode {} hit: {e}", category.as_str())))
+    }
+
+    async fn suggestion_hits<T: DeserializeOwned + Send + Sync + 'static>(
+        &self,
+        index: &str,
+        query: &str,
+    ) -> Result<Vec<T>, AppError> {
+        let index_handle = self.client.index(index);
+        let mut search = index_handle.search();
+        search
+            .with_query(query)
+            .with_limit(SUGGESTIONS_PER_CATEGORY)
+            .with_attributes_to_retrieve(Selectors::Some(&["titles", "name", "image_url"]));
+        let results = self.respond(index, search.execute::<T>()).await?;
+
+        Ok(results.hits.into_iter().map(|hit| hit.result).collect())
+    }
+}
+
+#[async_trait]
+impl SearchBackend for MeilisearchSearch {
+    fn name(&self) -> &'static str {
+        "meilisearch"
+    }
+
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        filters: &SearchFilters,
+        sort: Option<SearchSort>,
+        page: i32,
+    ) -> Result<(SearchHits<CanonicalMedia>, FacetCounts), AppError> {
+        let index = canonical_index_name(kind.into());
+        let query = search_query(query);
+        let filter = filters.filter_expression();
+        let sort_by = sort.map(SearchSort::expression);
+        let sort_by = sort_by.as_deref().map(|sort_by| [sort_by]);
+        let offset = page_offset(page);
+
+        let index_handle = self.client.index(index);
+        let mut search = index_handle.search();
+        search
+            .with_query(&query)
+            .with_offset(offset)
+            .with_limit(SEARCH_PAGE_SIZE)
+            .with_facets(Selectors::Some(&MEDIA_FACETS));
+        with_highlights(&mut search, &MEDIA_HIGHLIGHTED);
+        if let Some(filter) = &filter {
+            search.with_filter(filter);
+        }
+        if let Some(sort_by) = &sort_by {
+            search.with_sort(sort_by);
+        }
+        let results = self
+            .respond(index, search.execute::<CanonicalMedia>())
+            .await?;
+
+        let facets = results
+            .facet_distribution
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(facet, counts)| (facet, counts.into_iter().collect()))
+            .collect();
+        Ok((
+            Self::hits(results.hits, offset, results.estimated_total_hits),
+            facets,
+        ))
+    }
+
+    async fn search_characters(
+        &self,
+        provider: Provider,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError> {
+        let index = index_name(provider, Entity::Character);
+        let query = search_query(query);
+        let offset = page_offset(page);
+        let index_handle = self.client.index(&index);
+        let mut search = index_handle.search();
+        search
+            .with_query(&query)
+            .with_offset(offset)
+            .with_limit(SEARCH_PAGE_SIZE);
+        with_highlights(&mut search, &CHARACTER_HIGHLIGHTED);
+        let results = self.respond(&index, search.execute::<Character>()).await?;
+
+        Ok(Self::hits(
+            results.hits,
+            offset,
+            results.estimated_total_hits,
+        ))
+    }
+
+    async fn suggestions(
+        &self,
+        provider: Provider,
+        query: &str,
+    ) -> Result<Vec<Suggestion>, AppError> {
+        let character_index = index_name(provider, Entity::Character);
+        let query = search_query(query);
+        let (anime, manga, characters) = try_join!(
+            self.suggestion_hits::<MediaSuggestion>(canonical_index_name(Entity::Anime), &query),
+            self.suggestion_hits::<MediaSuggestion>(canonical_index_name(Entity::Manga), &query),
+            self.suggestion_hits::<CharacterSuggestion>(&character_index, &query),
+        )?;
+        let media = |hits: Vec<MediaSuggestion>| {
+            hits.into_iter()
+                .map(|hit| (hit.titles, hit.image_url))
+                .collect::<Vec<_>>()
+        };
+
+        Ok(media_suggestions(SearchCategory::Anime, media(anime))
+            .chain(media_suggestions(SearchCategory::Manga, media(manga)))
+            .chain(character_suggestions(
+                characters.into_iter().map(|hit| (hit.name, hit.image_url)),
+            ))
+            .collect())
+    }
+
+    // A single multi-search keeps the three indexes consistent with each other, and the
+    // ranking scores Meilisearch returns are normalised so they compare across indexes
+    async fn search_all(
+        &self,
+        provider: Provider,
+        query: &str,
+        filters: &SearchFilters,
+        page: i32,
+    ) -> Result<Vec<RankedGroup>, AppError> {
+        let query = search_query(query);
+        let filter = filters.filter_expression();
+        let offset = page_offset(page);
+        let character_uid = index_name(provider, Entity::Character);
+        let anime_index = self.client.index(canonical_index_name(Entity::Anime));
+        let manga_index = self.client.index(canonical_index_name(Entity::Manga));
+        let character_index = self.client.index(&character_uid);
+
+        let mut anime = anime_index.search();
+        anime
+            .with_query(&query)
+            .with_offset(offset)
+            .with_limit(SEARCH_PAGE_SIZE)
+            .with_show_ranking_score(true);
+        let mut manga = manga_index.search();
+        manga
+            .with_query(&query)
+            .with_offset(offset)
+            .with_limit(SEARCH_PAGE_SIZE)
+            .with_show_ranking_score(true);
+        with_highlights(&mut anime, &MEDIA_HIGHLIGHTED);
+        with_highlights(&mut manga, &MEDIA_HIGHLIGHTED);
+        if let Some(filter) = &filter {
+            anime.with_filter(filter);
+            manga.with_filter(filter);
+        }
+        let mut characters = character_index.search();
+        characters
+            .with_query(&query)
+            .with_offset(offset)
+            .with_limit(SEARCH_PAGE_SIZE)
+            .with_show_ranking_score(true);
+        with_highlights(&mut characters, &CHARACTER_HIGHLIGHTED);
+
+        let mut multi_search = self.client.multi_search();
+        multi_search
+            .with_search_query(anime)
+            .with_search_query(manga)
+            .with_search_query(characters);
+        let response = self
+            .respond("all indexes", multi_search.execute::<Value>())
+            .await?;
+
+        [
+            SearchCategory::Anime,
+            SearchCategory::Manga,
+            SearchCategory::Character,
+        ]
+        .into_iter()
+        .zip(response.results)
+        .map(|(category, results)| {
+            let items = results
+                .hits
+                .into_iter()
+                .map(|hit| {
+                    Ok((
+                        hit.ranking_score.unwrap_or_default(),
+                        Self::search_result(category, hit)?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, AppError>>()?;
+            Ok((
+                category,
+                SearchHits {
+                    has_next_page: results
+                        .estimated_total_hits
+                        .is_some_and(|total| offset + items.len() < total),
+                    total: results.estimated_total_hits,
+                    items,
+                },
+            ))
+        })
+        .collect()
+    }
+}
+
+pub struct PostgresSearch {
+    backend: PostgreSQLBackend,
+}
+
+impl PostgresSearch {
+    async fn ranked_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<CanonicalMedia>, AppError> {
+        let ranked = self
+            .backend
+            .search_media_ids(kind, query, i64::try_from(limit).unwrap_or(i64::MAX), 0)
+            .await?;
+        let identities = self.backend.identities_of(kind.into(), &ranked.ids).await?;
+        let mut records = Vec::new();
+        for (provider, ids) in linked_ids(&identities) {
+            records.extend(catalog_media(&self.backend, provider, kind, &ids).await?);
+        }
+        let mut media = merge_records(identities, records);
+        media.sort_by_key(|media| ranked.ids.iter().position(|id| *id == media.id));
+
+        Ok(media)
+    }
+
+    async fn ranked_characters(
+        &self,
+        provider: Provider,
+        query: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Character>, usize), AppError> {
+        let ranked = self
+            .backend
+            .search_character_ids(
+                provider,
+                query,
+                i64::try_from(limit).unwrap_or(i64::MAX),
+                i64::try_from(offset).unwrap_or(i64::MAX),
+            )
+            .await?;
+        let mut characters = catalog_characters(&self.backend, provider, &ranked.ids).await?;
+        characters.sort_by_key(|character| ranked.ids.iter().position(|id| *id == character.id));
+
+        Ok((characters, ranked.total))
+    }
+}
+
+#[async_trait]
+impl SearchBackend for PostgresSearch {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    // Filters, sorting and facets run over the best-ranked candidates rather than the whole
+    // catalog, which keeps the failover cheap enough to serve live traffic
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        query: &str,
+        filters: &SearchFilters,
+        sort: Option<SearchSort>,
+        page: i32,
+    ) -> Result<(SearchHits<CanonicalMedia>, FacetCounts), AppError> {
+        let mut media = self
+            .ranked_media(kind, query, POSTGRES_SEARCH_CANDIDATES)
+            .await?;
+        media.retain(|media| filters.matches(media));
+        if let Some(sort) = sort {
+            sort.apply(&mut media);
+        }
+        let facets = count_facets(&media);
+        let total = media.len();
+        let offset = page_offset(page);
+
+        Ok((
+            SearchHits {
+                items: media
+                    .into_iter()
+                    .skip(offset)
+                    .take(SEARCH_PAGE_SIZE)
+                    .collect(),
+                has_next_page: offset + SEARCH_PAGE_SIZE < total,
+                total: Some(total),
+            },
+            facets,
+        ))
+    }
+
+    async fn search_characters(
+        &self,
+        provider: Provider,
+        query: &str,
+        page: i32,
+    ) -> Result<SearchHits<Character>, AppError> {
+        let offset = page_offset(page);
+        let (characters, total) = self
+            .ranked_characters(provider, query, SEARCH_PAGE_SIZE, offset)
+            .await?;
+
+        Ok(SearchHits {
+            has_next_page: offset + characters.len() < total,
+            total: Some(total),
+            items: characters,
+        })
+    }
+
+    async fn suggestions(
+        &self,
+        provider: Provider,
+        query: &str,
+    ) -> Result<Vec<Suggestion>, AppError> {
+        let (anime, manga, (characters, _)) = try_join!(
+            self.ranked_media(MediaKind::Anime, query, SUGGESTIONS_PER_CATEGORY),
+            self.ranked_media(MediaKind::Manga, query, SUGGESTIONS_PER_CATEGORY),
+            self.ranked_characters(provider, query, SUGGESTIONS_PER_CATEGORY, 0),
+        )?;
+        let media = |media: Vec<CanonicalMedia>| {
+            media
+                .into_iter()
+                .map(|media| (media.titles, media.image_url))
+                .collect::<Vec<_>>()
+        };
+
+        Ok(media_suggestions(SearchCategory::Anime, media(anime))
+            .chain(media_suggestions(SearchCategory::Manga, media(manga)))
+            .chain(character_suggestions(
+                characters
+                    .into_iter()
+                    .map(|character| (character.name, character.image_url)),
+            ))
+            .collect())
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{
+    config::consts::{DID_YOU_MEAN_LIMIT, DID_YOU_MEAN_MIN_TOKEN_LENGTH},
+    db::backend::PostgreSQLBackend,
+    errors::error_template::AppError,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Known titles and the words they are made of, built from the catalog
+pub struct Vocabulary {
+    titles: Vec<(String, String)>,
+    tokens: HashMap<String, usize>,
+}
+
+impl Vocabulary {
+    pub async fn load(backend: &PostgreSQLBackend) -> Result<Self, AppError> {
+        let terms = backend.vocabulary_terms().await?;
+        let mut tokens = HashMap::new();
+        for token in terms.iter().flat_map(|term| words(term)) {
+            *tokens.entry(token).or_default() += 1;
+        }
+
+        Ok(Self {
+            titles: terms
+                .into_iter()
+                .map(|term| (term.to_lowercase(), term))
+                .collect(),
+            tokens,
+        })
+    }
+
+    /// Close titles first, then the query with each unknown word swapped for its nearest
+    /// known word
+    #[must_use]
+    pub fn corrections(&self, query: &str) -> Vec<String> {
+        let query = query.trim().to_lowercase();
+        let length = query.chars().count();
+        if length < DID_YOU_MEAN_MIN_TOKEN_LENGTH {
+            return Vec::new();
+        }
+
+        let max_distance = (length / 4).clamp(1, 3);
+        let mut ranked = BTreeMap::new();
+        for (lowercase, title) in &self.titles {
+            if lowercase.chars().count().abs_diff(length) > max_distance {
+                continue;
+            }
+            let distance = edit_distance(&query, lowercase);
+            if (1..=max_distance).contains(&distance) {
+                ranked
+                    .entry((distance, title.chars().count()))
+                    .or_insert_with(Vec::new)
+                    .push(title.clone());
+            }
+        }
+        let mut corrections: Vec<String> = ranked.into_values().flatten().collect();
+
+        corrections.extend(self.correct_words(&query));
+        let mut seen = Vec::new();
+        corrections.retain(|correction| {
+            let key = correction.to_lowercase();
+            let fresh = key != query && !seen.contains(&key);
+            seen.push(key);
+            fresh
+        });
+        corrections.truncate(DID_YOU_MEAN_LIMIT);
+        corrections
+    }
+
+    fn correct_words(&self, query: &str) -> Option<String> {
+        let mut changed = false;
+        let corrected = query
+            .split_whitespace()
+            .map(|word| {
+                if word.chars().count() < DID_YOU_MEAN_MIN_TOKEN_LENGTH
+                    || self.tokens.contains_key(word)
+                {
+                    return word.to_string();
+                }
+                let max_distance = if word.chars().count() < 8 { 1 } else { 2 };
+                self.tokens
+                    .iter()
+                    .filter(|(token, _)| {
+                        token.chars().count().abs_diff(word.chars().count()) <= max_distance
+                    })
+                    .map(|(token, count)| (edit_distance(word, token), *count, token))
+                    .filter(|(distance, _, _)| *distance <= max_distance)
+                    // Closest first, then the word the catalog uses most
+                    .min_by(|(a, a_count, a_token), (b, b_count, b_token)| {
+                        a.cmp(b)
+                            .then(b_count.cmp(a_count))
+                            .then(a_token.cmp(b_token))
+                    })
+                    .map_or_else(
+                        || word.to_string(),
+                        |(_, _, token)| {
+                            changed = true;
+                            token.clone()
+                        },
+                    )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        changed.then_some(corrected)
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= DID_YOU_MEAN_MIN_TOKEN_LENGTH)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance that also counts swapping two adjacent characters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = Vec::new();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut next_row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            next_row[j] = (row[j] + 1).min(next_row[j - 1] + 1).min(row[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                next_row[j] = next_row[j].min(previous_row[j - 2] + 1);
+            }
+        }
+        previous_row = std::mem::replace(&mut row, next_row);
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocabulary(terms: &[&str]) -> Vocabulary {
+        let mut tokens = HashMap::new();
+        for token in terms.iter().flat_map(|term| words(term)) {
+            *tokens.entry(token).or_default() += 1;
+        }
+        Vocabulary {
+            titles: terms
+                .iter()
+                .map(|term| (term.to_lowercase(), (*term).to_string()))
+                .collect(),
+            tokens,
+        }
+    }
+
+    #[test]
+    fn transpositions_are_one_edit() {
+        assert_eq!(edit_distance("freiren", "frieren"), 1);
+        assert_eq!(edit_distance("abdc", "abcd"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "naruto"), 6);
+        assert_eq!(edit_distance("naruto", "naruto"), 0);
+    }
+
+    #[test]
+    fn titles_far_off_in_length_are_skipped() {
+        let vocabulary = vocabulary(&["Frieren", "Frieren: Beyond Journey's End"]);
+        assert_eq!(vocabulary.corrections("Freiren"), vec!["Frieren"]);
+        assert!(vocabulary.corrections("frn").is_empty());
+    }
+
+    #[test]
+    fn unknown_words_are_corrected_one_by_one() {
+        let vocabulary = vocabulary(&["Attack on Titan", "Spy x Family"]);
+        assert_eq!(
+            vocabulary.corrections("attakc on the titan"),
+            vec!["attack on the titan"]
+        );
+        assert!(vocabulary.corrections("attack on titan").is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "ssr")]
+
+use otakuhub::{
+    apis::model::{MediaKind, Provider},
+    config::{
+        settings::{APISConfig, HttpMode, MainConfig, SearchConfig},
+        types::{UTILS_CONFIG, UtilsConfig},
+    },
+    utils::cache::media_by_id,
+};
+use std::sync::Arc;
+
+#[test]
+fn jikan_media_by_id_replays_fixture() {
+    UTILS_CONFIG
+        .set(Arc::new(UtilsConfig {
+            main: MainConfig {
+                log_level: "info".to_string(),
+                jaeger: String::new(),
+                site_name: "OtakuHub".to_string(),
+            },
+            admins: Vec::new(),
+            apis: APISConfig {
+                http_mode: HttpMode::Replay,
+                fixture_dir: concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures").to_string(),
+                ..APISConfig::default()
+            },
+            search: SearchConfig::default(),
+        }))
+        .expect("utils config is only set once");
+
+    let runtime = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let media = runtime
+        .block_on(media_by_id(Provider::Jikan, MediaKind::Anime, 1))
+        .expect("fixture replays")
+        .expect("fixture holds the anime");
+
+    assert_eq!(media.title(), "Cowboy Bebop");
+    assert_eq!(media.provider, Provider::Jikan);
+    assert_eq!(media.episodes, Some(26));
+    assert_eq!(media.popularity, Some(1_900_000));
+    assert_eq!(media.creators, ["Sunrise"]);
+    assert_eq!(media.tags, ["Adult Cast", "Space"]);
+}